@@ -1,26 +1,126 @@
+//! Runs an eldiro script given as a file, with `-e` or on standard input, and
+//! starts the REPL otherwise. Arguments after the script are bound to `args`
+//! as a list of strings.
+
+use std::fs;
+use std::io::{self, IsTerminal, Read};
+use std::process::ExitCode;
+
+use rustyline::error::ReadlineError;
 use rustyline::Editor;
 
-fn main() {
-    let mut rl = Editor::<()>::new();
+use validator::InputValidator;
+
+mod validator;
+
+const USAGE: &str = "usage: eldiro-cli [<script> | -e <program> | -] [args...]";
+
+/// Scripts run on a thread of their own so that deep recursion has more room
+/// than the main thread's stack gives it.
+const STACK_SIZE: usize = 64 * 1024 * 1024;
+
+fn main() -> ExitCode {
+    std::thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(run)
+        .expect("cannot start the interpreter thread")
+        .join()
+        .unwrap_or(ExitCode::FAILURE)
+}
+
+fn run() -> ExitCode {
+    let mut args = std::env::args().skip(1);
+
+    let first = args.next();
+    let source = match first.as_deref() {
+        None if io::stdin().is_terminal() => {
+            repl();
+            return ExitCode::SUCCESS;
+        }
+        None | Some("-") => read_stdin(),
+        Some("-e") => match args.next() {
+            Some(program) => Ok(program),
+            None => {
+                eprintln!("{}", USAGE);
+                return ExitCode::from(2);
+            }
+        },
+        Some(path) => {
+            fs::read_to_string(path).map_err(|err| format!("cannot read {}: {}", path, err))
+        }
+    };
+
+    let source = match source {
+        Ok(source) => source,
+        Err(msg) => {
+            eprintln!("eldiro-cli: {}", msg);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut env = env(args.collect());
+    match eval(&source, &mut env) {
+        Ok(Some(val)) => println!("{}", val),
+        Ok(None) => {}
+        Err(msg) => {
+            eprintln!("{}", msg);
+            return ExitCode::FAILURE;
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+fn read_stdin() -> Result<String, String> {
+    let mut source = String::new();
+    io::stdin()
+        .read_to_string(&mut source)
+        .map_err(|err| format!("cannot read standard input: {}", err))?;
+    Ok(source)
+}
+
+fn env(args: Vec<String>) -> eldiro::Env {
+    let mut env = eldiro::Env::default();
+    // Leaves room for the interpreter itself and for the REPL's editor.
+    env.set_stack_limit(STACK_SIZE - 4 * 1024 * 1024);
+    env.set_binding("args", args);
+    env
+}
+
+fn repl() {
+    let mut rl = Editor::<InputValidator>::new();
+    rl.set_helper(Some(InputValidator::default()));
     let history_file = ".eldiro_history";
     if rl.load_history(history_file).is_err() {
         println!("No previous history.");
     }
 
-    let mut env = eldiro::Env::default();
+    let mut env = env(Vec::new());
 
     loop {
-        let readline = rl.readline("→ ");
+        let validator = rl.helper().expect("the REPL always has a validator");
+        let prompt = if validator.is_continuing() {
+            "… "
+        } else {
+            "→ "
+        };
+
+        let readline = rl.readline(prompt);
+        let validator = rl.helper_mut().expect("the REPL always has a validator");
         match readline {
             Ok(line) => {
-                let line = line.as_str().trim();
-                rl.add_history_entry(line);
-                match eval(line, &mut env) {
+                let input = match validator.push_line(&line) {
+                    Some(input) => input,
+                    None => continue,
+                };
+                rl.add_history_entry(input.trim());
+                match eval(&input, &mut env) {
                     Ok(Some(val)) => println!("{}", val),
                     Ok(None) => {}
                     Err(msg) => println!("{}", msg),
                 }
             }
+            // Ctrl-C abandons an unfinished entry rather than the REPL.
+            Err(ReadlineError::Interrupted) if validator.is_continuing() => validator.clear(),
             Err(err) => {
                 println!("{:?}", err);
                 break;
@@ -31,11 +131,9 @@ fn main() {
 }
 
 fn eval(input: &str, env: &mut eldiro::Env) -> Result<Option<eldiro::Val>, String> {
-    let parse = eldiro::parse(input).map_err(|msg| format!("Parse error: {}", msg))?;
+    let parse = eldiro::parse(input).map_err(|err| err.render(input))?;
 
-    let evaluated = parse
-        .eval(env)
-        .map_err(|msg| format!("Evaluation error: {}", msg))?;
+    let evaluated = parse.eval(env).map_err(|err| err.render(&parse))?;
 
     if evaluated == eldiro::Val::Unit {
         Ok(None)
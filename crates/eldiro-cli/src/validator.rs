@@ -0,0 +1,177 @@
+use eldiro::{Token, TokenKind};
+use rustyline::completion::Completer;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::validate::{ValidationContext, ValidationResult, Validator};
+use rustyline::Helper;
+
+/// Punctuation that cannot end a statement, because something has to follow
+/// it.
+const CONTINUING: &[&str] = &[
+    "+", "-", "*", "/", "==", "!=", "<", "<=", ">", ">=", "&&", "||", "!", "=", "+=", "-=", "*=",
+    "/=", "=>", ",", ":", ".", "|",
+];
+
+/// Collects the lines of one REPL entry until it is complete.
+///
+/// Rustyline only draws a prompt before the first line of its buffer, so
+/// incomplete input is accepted line by line and kept here, letting the REPL
+/// ask for the rest with a continuation prompt.
+#[derive(Default)]
+pub(crate) struct InputValidator {
+    pending: String,
+}
+
+impl InputValidator {
+    pub(crate) fn is_continuing(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Adds `line` to the entry, giving back the whole entry once it is
+    /// complete. A blank line ends the entry regardless, so that a mistake
+    /// can be evaluated and reported instead of asking for more forever.
+    pub(crate) fn push_line(&mut self, line: &str) -> Option<String> {
+        let force = line.trim().is_empty() && self.is_continuing();
+
+        self.pending.push_str(line);
+        self.pending.push('\n');
+
+        match validate_input(&self.pending) {
+            ValidationResult::Incomplete if !force => None,
+            _ => Some(std::mem::take(&mut self.pending)),
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.pending.clear();
+    }
+}
+
+impl Validator for InputValidator {
+    fn validate(&self, ctx: &mut ValidationContext) -> rustyline::Result<ValidationResult> {
+        let input = format!("{}{}", self.pending, ctx.input());
+
+        Ok(match validate_input(&input) {
+            // Accepted so that `push_line` can keep it for the next prompt.
+            ValidationResult::Incomplete => ValidationResult::Valid(None),
+            result => result,
+        })
+    }
+}
+
+impl Completer for InputValidator {
+    type Candidate = String;
+}
+
+impl Hinter for InputValidator {
+    type Hint = String;
+}
+
+impl Highlighter for InputValidator {}
+
+impl Helper for InputValidator {}
+
+/// Input is incomplete while a bracket, string or block comment is left open,
+/// or when it ends in an operator. A closing bracket without a matching
+/// opening one can never be completed.
+fn validate_input(input: &str) -> ValidationResult {
+    let tokens = match eldiro::tokenize(input) {
+        Ok(tokens) => tokens,
+        Err(error) if error.span.start == input.len() => return ValidationResult::Incomplete,
+        // Anything else is left for the parser to report.
+        Err(_) => return ValidationResult::Valid(None),
+    };
+
+    let mut open = Vec::new();
+    for token in tokens.iter().filter(|token| token.kind == TokenKind::Punct) {
+        match token.text {
+            "(" | "[" | "{" => open.push(token.text),
+            ")" | "]" | "}" => match (open.pop(), token.text) {
+                (Some("("), ")") | (Some("["), "]") | (Some("{"), "}") => {}
+                _ => {
+                    return ValidationResult::Invalid(Some(format!("  unmatched `{}`", token.text)))
+                }
+            },
+            _ => {}
+        }
+    }
+
+    let ends_with_operator = tokens
+        .iter()
+        .rfind(|token| token.kind != TokenKind::Newline)
+        .is_some_and(|Token { kind, text, .. }| {
+            *kind == TokenKind::Punct && CONTINUING.contains(text)
+        });
+
+    if open.is_empty() && !ends_with_operator {
+        ValidationResult::Valid(None)
+    } else {
+        ValidationResult::Incomplete
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_incomplete(input: &str) -> bool {
+        matches!(validate_input(input), ValidationResult::Incomplete)
+    }
+
+    #[test]
+    fn complete_input() {
+        assert!(!is_incomplete("let a = 1"));
+        assert!(!is_incomplete("fn f x => { x }\n"));
+        assert!(!is_incomplete(""));
+    }
+
+    #[test]
+    fn incomplete_with_open_brackets() {
+        assert!(is_incomplete("fn f x => {"));
+        assert!(is_incomplete("[1, (2"));
+        assert!(is_incomplete("{\n  let a = [\n    1\n  ]\n"));
+    }
+
+    #[test]
+    fn incomplete_with_trailing_operator() {
+        assert!(is_incomplete("1 +"));
+        assert!(is_incomplete("let a =\n"));
+        assert!(is_incomplete("map (|x|"));
+    }
+
+    #[test]
+    fn incomplete_with_open_string_or_comment() {
+        assert!(is_incomplete("\"abc"));
+        assert!(is_incomplete("1 /* note"));
+    }
+
+    #[test]
+    fn invalid_with_unmatched_close() {
+        assert!(matches!(
+            validate_input("1 + 2)"),
+            ValidationResult::Invalid(Some(_)),
+        ));
+    }
+
+    #[test]
+    fn push_lines_until_complete() {
+        let mut validator = InputValidator::default();
+
+        assert_eq!(validator.push_line("fn f x => {"), None);
+        assert!(validator.is_continuing());
+        assert_eq!(validator.push_line("  x + 1"), None);
+        assert_eq!(
+            validator.push_line("}"),
+            Some("fn f x => {\n  x + 1\n}\n".to_string()),
+        );
+        assert!(!validator.is_continuing());
+    }
+
+    #[test]
+    fn blank_line_ends_incomplete_input() {
+        let mut validator = InputValidator::default();
+
+        assert_eq!(validator.push_line("1 +"), None);
+        assert_eq!(validator.push_line(""), Some("1 +\n\n".to_string()));
+    }
+}
@@ -0,0 +1,147 @@
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+
+fn run(args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_eldiro-cli"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: &Output) -> &str {
+    std::str::from_utf8(&output.stdout).unwrap()
+}
+
+fn stderr(output: &Output) -> &str {
+    std::str::from_utf8(&output.stderr).unwrap()
+}
+
+/// A script file that is removed again when the test ends.
+struct Script(PathBuf);
+
+impl Script {
+    fn new(name: &str, source: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("eldiro-cli-{}-{}", std::process::id(), name));
+        fs::write(&path, source).unwrap();
+        Self(path)
+    }
+
+    fn path(&self) -> &str {
+        self.0.to_str().unwrap()
+    }
+}
+
+impl Drop for Script {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+#[test]
+fn run_program_given_with_e() {
+    let output = run(&["-e", "1 + 2"], "");
+
+    assert_eq!(stdout(&output), "3\n");
+    assert_eq!(stderr(&output), "");
+    assert_eq!(output.status.code(), Some(0));
+}
+
+#[test]
+fn bind_remaining_args() {
+    let output = run(&["-e", "args", "a", "b c"], "");
+
+    assert_eq!(stdout(&output), "[\"a\", \"b c\"]\n");
+    assert_eq!(output.status.code(), Some(0));
+}
+
+#[test]
+fn run_script_file() {
+    let script = Script::new(
+        "script.eld",
+        "let a = 2\nfn double x => x * 2\ndouble a + len args\n",
+    );
+    let output = run(&[script.path(), "x"], "");
+
+    assert_eq!(stdout(&output), "5\n");
+    assert_eq!(stderr(&output), "");
+    assert_eq!(output.status.code(), Some(0));
+}
+
+#[test]
+fn run_program_from_piped_stdin() {
+    let output = run(&[], "2 * 3\n");
+
+    assert_eq!(stdout(&output), "6\n");
+    assert_eq!(output.status.code(), Some(0));
+}
+
+#[test]
+fn run_program_from_stdin_with_dash() {
+    let output = run(&["-", "x", "y"], "len args\n");
+
+    assert_eq!(stdout(&output), "2\n");
+    assert_eq!(output.status.code(), Some(0));
+}
+
+#[test]
+fn print_nothing_for_unit() {
+    let output = run(&["-e", "let a = 1"], "");
+
+    assert_eq!(stdout(&output), "");
+    assert_eq!(output.status.code(), Some(0));
+}
+
+#[test]
+fn fail_with_usage_when_e_has_no_program() {
+    let output = run(&["-e"], "");
+
+    assert_eq!(stdout(&output), "");
+    assert_eq!(
+        stderr(&output),
+        "usage: eldiro-cli [<script> | -e <program> | -] [args...]\n",
+    );
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn report_parse_error() {
+    let output = run(&["-e", "1 +"], "");
+
+    assert_eq!(stdout(&output), "");
+    assert!(stderr(&output).starts_with("error: expected"));
+    assert!(stderr(&output).contains("1 | 1 +\n"));
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn report_eval_error() {
+    let output = run(&["-e", "1 / 0"], "");
+
+    assert_eq!(stdout(&output), "");
+    assert!(stderr(&output).starts_with("error: "));
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn report_missing_script() {
+    let script = Script::new("missing.eld", "");
+    let path = script.path().to_string();
+    drop(script);
+    let output = run(&[&path], "");
+
+    assert!(stderr(&output).starts_with(&format!("eldiro-cli: cannot read {}: ", path)));
+    assert_eq!(output.status.code(), Some(1));
+}
@@ -0,0 +1,214 @@
+use crate::env::Env;
+use crate::error::{EvalError, ParseError, Span};
+use crate::expr::{Expr, Op};
+use crate::signal::Signal;
+use crate::utils::{self, Input, OrParse};
+use crate::val::Val;
+
+/// Updates a binding defined by an earlier `let`, possibly in an enclosing
+/// scope. `x += 1` is short for `x = x + 1`.
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) struct Assignment {
+    pub(crate) name: String,
+    pub(crate) op: Option<Op>,
+    pub(crate) val: Expr,
+    pub(crate) span: Span,
+}
+
+impl Assignment {
+    pub(crate) fn new(s: Input) -> Result<(Input, Self), ParseError> {
+        let start = s;
+
+        let (s, name) = utils::extract_ident(s)?;
+        let (s, _) = utils::extract_non_breaks(s);
+
+        let (s, op) = Self::new_op(s)?;
+        let (s, _) = utils::extract_whitespace(s);
+
+        let (s, val) = Expr::new(s)?;
+
+        Ok((
+            s,
+            Self {
+                name: name.to_string(),
+                op,
+                val,
+                span: start.span_to(s),
+            },
+        ))
+    }
+
+    fn new_op(s: Input) -> Result<(Input, Option<Op>), ParseError> {
+        utils::tag("+=", s)
+            .map(|s| (s, Some(Op::Add)))
+            .or_parse(|| utils::tag("-=", s).map(|s| (s, Some(Op::Sub))))
+            .or_parse(|| utils::tag("*=", s).map(|s| (s, Some(Op::Mul))))
+            .or_parse(|| utils::tag("/=", s).map(|s| (s, Some(Op::Div))))
+            .or_parse(|| utils::tag("=", s).map(|s| (s, None)))
+    }
+
+    pub(crate) fn eval(&self, env: &mut Env) -> Result<Val, Signal> {
+        let unbound = || EvalError::UnboundName {
+            name: self.name.clone(),
+            span: self.span,
+        };
+
+        let val = match &self.op {
+            Some(op) => {
+                let current = env.get_binding(&self.name).ok_or_else(unbound)?;
+                op.eval(current, || self.val.eval(env), env.arithmetic(), self.span)?
+            }
+            None => self.val.eval(env)?,
+        };
+
+        if env.update_binding(&self.name, val) {
+            Ok(Val::Unit)
+        } else {
+            Err(unbound().into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::expr::{BindingUsage, Number};
+    use crate::stmt::Stmt;
+
+    use super::*;
+
+    #[test]
+    fn parse_assignment() {
+        assert_eq!(
+            Assignment::new("x = y".into()),
+            Ok((
+                "".into(),
+                Assignment {
+                    name: "x".to_string(),
+                    op: None,
+                    val: Expr::BindingUsage(BindingUsage {
+                        name: "y".to_string(),
+                        span: Span::new(4, 5),
+                    }),
+                    span: Span::new(0, 5),
+                },
+            )),
+        );
+    }
+
+    #[test]
+    fn parse_compound_assignment() {
+        assert_eq!(
+            Assignment::new("total *= 2".into()),
+            Ok((
+                "".into(),
+                Assignment {
+                    name: "total".to_string(),
+                    op: Some(Op::Mul),
+                    val: Expr::Number(Number::from(2)),
+                    span: Span::new(0, 10),
+                },
+            )),
+        );
+    }
+
+    #[test]
+    fn cannot_parse_comparison_as_assignment() {
+        assert_eq!(
+            Assignment::new("x == 1".into()),
+            Err(ParseError {
+                span: Span::new(2, 3),
+                line: 1,
+                column: 3,
+                expected: vec![
+                    "`*=`".to_string(),
+                    "`+=`".to_string(),
+                    "`-=`".to_string(),
+                    "`/=`".to_string(),
+                    "`=`".to_string(),
+                ],
+            }),
+        );
+    }
+
+    #[test]
+    fn eval_assignment() {
+        let mut env = Env::default();
+        env.store_binding("x".to_string(), Val::from(1));
+
+        let (_, assignment) = Assignment::new("x = 5".into()).unwrap();
+        assert_eq!(assignment.eval(&mut env), Ok(Val::Unit));
+        assert_eq!(env.get_binding("x"), Some(Val::from(5)));
+    }
+
+    #[test]
+    fn eval_compound_assignment() {
+        let mut env = Env::default();
+        env.store_binding("x".to_string(), Val::from(10));
+
+        let (_, assignment) = Assignment::new("x -= 4".into()).unwrap();
+        assert_eq!(assignment.eval(&mut env), Ok(Val::Unit));
+        assert_eq!(env.get_binding("x"), Some(Val::from(6)));
+    }
+
+    #[test]
+    fn eval_assignment_updates_enclosing_scope() {
+        let mut env = Env::default();
+        env.store_binding("x".to_string(), Val::from(1));
+
+        let (_, block) = Stmt::new("{ x += 1 }".into()).unwrap();
+        block.eval(&mut env).unwrap();
+
+        assert_eq!(env.get_binding("x"), Some(Val::from(2)));
+    }
+
+    #[test]
+    fn eval_assignment_updates_nearest_shadowing_binding() {
+        let mut env = Env::default();
+        env.store_binding("x".to_string(), Val::from(1));
+
+        let (_, block) = Stmt::new("{ let x = 10\n x = 20 }".into()).unwrap();
+        block.eval(&mut env).unwrap();
+
+        assert_eq!(env.get_binding("x"), Some(Val::from(1)));
+    }
+
+    #[test]
+    fn eval_assignment_to_undefined_binding() {
+        let (_, assignment) = Assignment::new("x = 1".into()).unwrap();
+
+        assert_eq!(
+            assignment.eval(&mut Env::default()),
+            Err(EvalError::UnboundName {
+                name: "x".to_string(),
+                span: Span::new(0, 5),
+            }
+            .into()),
+        );
+    }
+
+    #[test]
+    fn eval_compound_assignment_to_undefined_binding() {
+        let (_, assignment) = Assignment::new("x += 1".into()).unwrap();
+
+        assert_eq!(
+            assignment.eval(&mut Env::default()),
+            Err(EvalError::UnboundName {
+                name: "x".to_string(),
+                span: Span::new(0, 6),
+            }
+            .into()),
+        );
+    }
+
+    #[test]
+    fn eval_while_loop_with_counter() {
+        let mut env = Env::default();
+        let (_, def) = Stmt::new("let i = 0".into()).unwrap();
+        def.eval(&mut env).unwrap();
+
+        let (_, stmt) = Stmt::new("while i < 10 { i += 1 }".into()).unwrap();
+        stmt.eval(&mut env).unwrap();
+
+        assert_eq!(env.get_binding("i"), Some(Val::from(10)));
+    }
+}
@@ -1,6 +1,8 @@
 use crate::env::Env;
+use crate::error::ParseError;
 use crate::expr::Expr;
-use crate::utils;
+use crate::signal::Signal;
+use crate::utils::{self, Input};
 use crate::val::Val;
 
 #[derive(Debug, PartialEq, Clone)]
@@ -10,8 +12,8 @@ pub(crate) struct BindingDef {
 }
 
 impl BindingDef {
-    pub(crate) fn new(s: &str) -> Result<(&str, Self), String> {
-        let s = utils::tag("let", s)?;
+    pub(crate) fn new(s: Input) -> Result<(Input, Self), ParseError> {
+        let s = utils::keyword("let", s)?;
         let (s, _) = utils::extract_whitespace1(s)?;
 
         let (s, name) = utils::extract_ident(s)?;
@@ -31,7 +33,7 @@ impl BindingDef {
         ))
     }
 
-    pub(crate) fn eval(&self, env: &mut Env) -> Result<Val, String> {
+    pub(crate) fn eval(&self, env: &mut Env) -> Result<Val, Signal> {
         let val = self.val.eval(env)?;
         env.store_binding(self.name.clone(), val);
         Ok(Val::Unit)
@@ -40,6 +42,7 @@ impl BindingDef {
 
 #[cfg(test)]
 mod tests {
+    use crate::error::Span;
     use crate::expr::{Number, Op};
 
     use super::*;
@@ -47,15 +50,16 @@ mod tests {
     #[test]
     fn parse_binding_def() {
         assert_eq!(
-            BindingDef::new("let a = 10 / 2"),
+            BindingDef::new("let a = 10 / 2".into()),
             Ok((
-                "",
+                "".into(),
                 BindingDef {
                     name: "a".to_string(),
                     val: Expr::Operation {
-                        lhs: Box::new(Expr::Number(Number(10))),
-                        rhs: Box::new(Expr::Number(Number(2))),
+                        lhs: Box::new(Expr::Number(Number::from(10))),
+                        rhs: Box::new(Expr::Number(Number::from(2))),
                         op: Op::Div,
+                        span: Span::new(8, 14),
                     },
                 },
             )),
@@ -65,12 +69,12 @@ mod tests {
     #[test]
     fn parse_more_binding_def() {
         assert_eq!(
-            BindingDef::new("let a123a=121"),
+            BindingDef::new("let a123a=121".into()),
             Ok((
-                "",
+                "".into(),
                 BindingDef {
                     name: "a123a".to_string(),
-                    val: Expr::Number(Number(121)),
+                    val: Expr::Number(Number::from(121)),
                 },
             )),
         );
@@ -79,8 +83,26 @@ mod tests {
     #[test]
     fn cannot_parse_binding_def_without_space_after_let() {
         assert_eq!(
-            BindingDef::new("letaaa=1+2"),
-            Err("expected a space".to_string()),
+            BindingDef::new("letaaa=1+2".into()),
+            Err(ParseError {
+                span: Span::new(0, 1),
+                line: 1,
+                column: 1,
+                expected: vec!["`let`".to_string()],
+            }),
+        );
+    }
+
+    #[test]
+    fn cannot_bind_keyword() {
+        assert_eq!(
+            BindingDef::new("let if = 1".into()),
+            Err(ParseError {
+                span: Span::new(4, 5),
+                line: 1,
+                column: 5,
+                expected: vec!["identifier".to_string()],
+            }),
         );
     }
 }
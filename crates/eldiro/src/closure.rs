@@ -0,0 +1,111 @@
+use std::fmt;
+use std::rc::Rc;
+
+use crate::env::{Captured, Env};
+use crate::error::{EvalError, Span};
+use crate::signal::Signal;
+use crate::stmt::Stmt;
+use crate::val::Val;
+
+/// A function together with the scope it was defined in, which is where its
+/// body looks up names that are not parameters.
+#[derive(Clone)]
+pub struct Closure {
+    pub(crate) params: Vec<String>,
+    pub(crate) body: Rc<Stmt>,
+    pub(crate) scope: Captured,
+}
+
+impl Closure {
+    /// Runs the body with `args` bound to the parameters. The caller has to
+    /// make sure the number of arguments matches.
+    ///
+    /// A call in tail position of the body is not evaluated by the body
+    /// itself but handed back here, so that tail recursion runs in a loop
+    /// instead of on the native stack.
+    pub(crate) fn call(
+        self: Rc<Self>,
+        args: Vec<Val>,
+        caller: &Env,
+        span: Span,
+    ) -> Result<Val, Signal> {
+        caller.check_stack(span)?;
+        if caller.call_depth_exceeded() {
+            return Err(EvalError::StackOverflow {
+                limit: caller.max_call_depth(),
+                span,
+            }
+            .into());
+        }
+
+        let (mut closure, mut args) = (self, args);
+        loop {
+            let mut env = caller.create_call(&closure.scope);
+            for (param, arg) in closure.params.iter().zip(args) {
+                env.store_binding(param.clone(), arg);
+            }
+
+            let tail = closure
+                .body
+                .eval_tail(&mut env)
+                .map_err(|signal| Signal::from(signal.into_error()))?;
+
+            match tail {
+                Tail::Val(val) => return Ok(val),
+                Tail::Call {
+                    closure: next,
+                    args: next_args,
+                    ..
+                } => {
+                    closure = next;
+                    args = next_args;
+                }
+            }
+        }
+    }
+}
+
+/// The result of evaluating an expression in tail position, where a call can
+/// be left for the caller to make.
+#[derive(Debug)]
+pub(crate) enum Tail {
+    Val(Val),
+    Call {
+        closure: Rc<Closure>,
+        args: Vec<Val>,
+        span: Span,
+    },
+}
+
+impl Tail {
+    /// Makes the pending call, if any, from `caller`.
+    pub(crate) fn finish(self, caller: &Env) -> Result<Val, Signal> {
+        match self {
+            Self::Val(val) => Ok(val),
+            Self::Call {
+                closure,
+                args,
+                span,
+            } => closure.call(args, caller, span),
+        }
+    }
+}
+
+// The captured scope usually contains the closure itself, so it is compared by
+// identity and left out of debug output.
+impl PartialEq for Closure {
+    fn eq(&self, other: &Self) -> bool {
+        self.params == other.params
+            && self.body == other.body
+            && self.scope.same_scope(&other.scope)
+    }
+}
+
+impl fmt::Debug for Closure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Closure")
+            .field("params", &self.params)
+            .field("body", &self.body)
+            .finish_non_exhaustive()
+    }
+}
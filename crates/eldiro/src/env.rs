@@ -1,70 +1,621 @@
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
+use std::fmt;
+use std::ptr;
+use std::rc::{Rc, Weak};
 
+use crate::closure::Closure;
+use crate::error::{EvalError, Span};
+use crate::native::NativeFunc;
+use crate::prelude;
 use crate::stmt::Stmt;
 use crate::val::Val;
 
-#[derive(Debug, PartialEq, Clone)]
-enum NamedInfo {
-    Binding(Val),
-    Func { params: Vec<String>, body: Stmt },
+/// How integer arithmetic behaves when a result does not fit. With the
+/// `bigint` feature every result fits, so the choice makes no difference.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Arithmetic {
+    /// Overflow is reported as an evaluation error.
+    #[default]
+    Checked,
+    /// Results wrap around at the numeric bounds.
+    Wrapping,
+    /// Results are clamped to the numeric bounds.
+    Saturating,
 }
 
-impl NamedInfo {
-    fn into_binding(self) -> Option<Val> {
-        if let Self::Binding(val) = self {
-            Some(val)
+#[derive(Debug, PartialEq, Default)]
+pub(crate) struct Scope {
+    bindings: HashMap<String, Val>,
+    parent: Option<Rc<RefCell<Scope>>>,
+}
+
+impl Scope {
+    fn chain_lookup(&self, name: &str) -> Option<Val> {
+        self.bindings
+            .get(name)
+            .cloned()
+            .map(strengthen)
+            .or_else(|| {
+                self.parent
+                    .as_ref()
+                    .and_then(|parent| parent.borrow().chain_lookup(name))
+            })
+    }
+
+    fn chain_update(&mut self, name: &str, val: Val) -> bool {
+        if self.bindings.contains_key(name) {
+            let val = weaken(val, self);
+            self.bindings.insert(name.to_string(), val);
+            true
         } else {
-            None
+            self.parent
+                .as_ref()
+                .is_some_and(|parent| parent.borrow_mut().chain_update(name, val))
         }
     }
+}
 
-    fn into_func(self) -> Option<(Vec<String>, Stmt)> {
-        if let Self::Func { params, body } = self {
-            Some((params, body))
-        } else {
-            None
+/// The scope a closure was defined in.
+///
+/// A closure bound in the very scope it captures refers to it weakly, since
+/// the scope already keeps the closure alive and a strong reference back would
+/// form a cycle that is never freed. Reading the closure out of the scope makes
+/// the reference strong again, so that it can outlive the scope.
+///
+/// A closure bound in a scope enclosing the one it captures, such as one
+/// returned by a call and bound at the top level, still holds on to that scope
+/// and its parents until the binding is replaced or removed. The cycle this
+/// forms is broken by [`Scopes`] once the environment is dropped.
+#[derive(Clone)]
+pub(crate) enum Captured {
+    Strong(Rc<RefCell<Scope>>),
+    Weak(Weak<RefCell<Scope>>),
+}
+
+impl Captured {
+    pub(crate) fn same_scope(&self, other: &Self) -> bool {
+        ptr::eq(self.as_ptr(), other.as_ptr())
+    }
+
+    fn as_ptr(&self) -> *const RefCell<Scope> {
+        match self {
+            Self::Strong(scope) => Rc::as_ptr(scope),
+            Self::Weak(scope) => scope.as_ptr(),
         }
     }
 }
 
-#[derive(Debug, PartialEq, Default)]
-pub struct Env<'parent> {
-    bindings: HashMap<String, NamedInfo>,
-    parent: Option<&'parent Self>,
+/// Makes closures that capture `scope` refer to it weakly, before they are
+/// stored there. Closures inside lists and maps are included.
+fn weaken(val: Val, scope: &Scope) -> Val {
+    match val {
+        Val::Func(closure) => match &closure.scope {
+            Captured::Strong(captured) if ptr::eq(captured.as_ptr(), scope) => {
+                let scope = Captured::Weak(Rc::downgrade(captured));
+                Val::Func(Rc::new(Closure {
+                    scope,
+                    ..(*closure).clone()
+                }))
+            }
+            _ => Val::Func(closure),
+        },
+        Val::List(items) => Val::List(items.into_iter().map(|item| weaken(item, scope)).collect()),
+        Val::Map(entries) => Val::Map(
+            entries
+                .into_iter()
+                .map(|(key, val)| (key, weaken(val, scope)))
+                .collect(),
+        ),
+        val => val,
+    }
 }
 
-impl<'parent> Env<'parent> {
-    pub(crate) fn create_child(&'parent self) -> Self {
-        Self {
+/// Undoes `weaken` for a value read out of a scope, which is still alive.
+fn strengthen(val: Val) -> Val {
+    match val {
+        Val::Func(closure) => match &closure.scope {
+            Captured::Weak(captured) => match captured.upgrade() {
+                Some(captured) => Val::Func(Rc::new(Closure {
+                    scope: Captured::Strong(captured),
+                    ..(*closure).clone()
+                })),
+                None => Val::Func(closure),
+            },
+            Captured::Strong(_) => Val::Func(closure),
+        },
+        Val::List(items) => Val::List(items.into_iter().map(strengthen).collect()),
+        Val::Map(entries) => Val::Map(
+            entries
+                .into_iter()
+                .map(|(key, val)| (key, strengthen(val)))
+                .collect(),
+        ),
+        val => val,
+    }
+}
+
+/// Every scope created for an environment and the ones derived from it.
+///
+/// Scopes refer to their parents and closures to the scope they captured, so
+/// binding a closure anywhere below that scope forms a cycle. Once the last
+/// `Env` sharing the registry is dropped, the scopes still alive are emptied,
+/// which frees them along with every value bound in them.
+#[derive(Default)]
+struct Scopes {
+    scopes: RefCell<Vec<Weak<RefCell<Scope>>>>,
+    /// How many scopes were alive when the registry was last pruned.
+    live: Cell<usize>,
+}
+
+impl Scopes {
+    fn create(&self, parent: Option<Rc<RefCell<Scope>>>) -> Rc<RefCell<Scope>> {
+        let scope = Rc::new(RefCell::new(Scope {
             bindings: HashMap::new(),
-            parent: Some(self),
+            parent,
+        }));
+
+        // Pruning only once the registry has doubled keeps the cost of
+        // registering a scope constant on average.
+        let mut scopes = self.scopes.borrow_mut();
+        if scopes.len() >= 2 * self.live.get().max(16) {
+            scopes.retain(|scope| scope.strong_count() > 0);
+            self.live.set(scopes.len());
+        }
+        scopes.push(Rc::downgrade(&scope));
+
+        scope
+    }
+}
+
+impl Drop for Scopes {
+    fn drop(&mut self) {
+        for scope in self.scopes.get_mut().drain(..) {
+            if let Some(scope) = scope.upgrade() {
+                // Taken out first, so that the values are dropped without the
+                // scope being borrowed.
+                let emptied = std::mem::take(&mut *scope.borrow_mut());
+                drop(emptied);
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Scopes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Scopes").finish_non_exhaustive()
+    }
+}
+
+/// Which registry an environment belongs to says nothing about what is
+/// defined in it.
+impl PartialEq for Scopes {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+/// The address of a local, which is as close to the stack pointer as safe
+/// code can get.
+#[inline(never)]
+fn stack_position() -> usize {
+    let marker = 0u8;
+    std::hint::black_box(&marker) as *const u8 as usize
+}
+
+/// Scopes are shared with the children created from them, so that a child
+/// can update bindings that live further up the chain. Cloning an `Env` hands
+/// out another reference to the same scopes rather than copying them.
+///
+/// Dropping the last of them frees every scope, even those still captured by
+/// closures that were handed out, which then no longer see their bindings.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Env {
+    scope: Rc<RefCell<Scope>>,
+    scopes: Rc<Scopes>,
+    arithmetic: Arithmetic,
+    call_depth: usize,
+    max_call_depth: usize,
+    /// Where the native stack was when evaluation started, or zero outside of
+    /// [`Program::eval`](crate::Program::eval).
+    stack_base: usize,
+    stack_limit: usize,
+}
+
+/// Starts out with the prelude of builtin functions defined in a parent
+/// scope, so that they can be shadowed and are left out of
+/// [`Env::bindings`] and [`Env::functions`].
+impl Default for Env {
+    fn default() -> Self {
+        let mut prelude = Self::without_prelude();
+        prelude::register(&mut prelude);
+        prelude.create_child()
+    }
+}
+
+impl Env {
+    /// Catches runaway recursion well before it becomes slow. How deep
+    /// recursion can actually go is usually bounded by the stack limit first.
+    pub const DEFAULT_MAX_CALL_DEPTH: usize = 10_000;
+
+    /// Half of the 2 MiB stack Rust gives spawned threads by default, leaving
+    /// the rest to the host. A call needs about 2 KiB of stack in release
+    /// builds and several times that in debug builds.
+    pub const DEFAULT_STACK_LIMIT: usize = 1024 * 1024;
+
+    /// An environment with nothing defined in it, not even the builtin
+    /// functions of the prelude.
+    pub fn without_prelude() -> Self {
+        let scopes = Rc::new(Scopes::default());
+        Self {
+            scope: scopes.create(None),
+            scopes,
+            arithmetic: Arithmetic::default(),
+            call_depth: 0,
+            max_call_depth: Self::DEFAULT_MAX_CALL_DEPTH,
+            stack_base: 0,
+            stack_limit: Self::DEFAULT_STACK_LIMIT,
+        }
+    }
+
+    pub(crate) fn create_child(&self) -> Self {
+        Self {
+            scope: self.child_scope(),
+            scopes: Rc::clone(&self.scopes),
+            ..*self
+        }
+    }
+
+    /// A scope for the body of a function that captured `captured`, called
+    /// from this environment.
+    pub(crate) fn create_call(&self, captured: &Captured) -> Self {
+        let parent = match captured {
+            Captured::Strong(scope) => Rc::clone(scope),
+            Captured::Weak(scope) => scope
+                .upgrade()
+                .expect("closures are only called after being read out of their scope"),
+        };
+
+        Self {
+            scope: self.scopes.create(Some(parent)),
+            scopes: Rc::clone(&self.scopes),
+            call_depth: self.call_depth + 1,
+            ..*self
+        }
+    }
+
+    fn child_scope(&self) -> Rc<RefCell<Scope>> {
+        self.scopes.create(Some(Rc::clone(&self.scope)))
+    }
+
+    /// This scope, for a closure defined here to capture.
+    pub(crate) fn capture(&self) -> Captured {
+        Captured::Strong(Rc::clone(&self.scope))
+    }
+
+    /// Selects how overflowing arithmetic is handled in this environment and
+    /// every scope created from it. Division by zero is always an error.
+    pub fn set_arithmetic(&mut self, arithmetic: Arithmetic) {
+        self.arithmetic = arithmetic;
+    }
+
+    pub(crate) fn arithmetic(&self) -> Arithmetic {
+        self.arithmetic
+    }
+
+    /// Limits how many function calls can be nested before evaluation fails
+    /// with [`EvalError::StackOverflow`](crate::EvalError::StackOverflow).
+    /// Calls in tail position do not count towards the limit.
+    pub fn set_max_call_depth(&mut self, max_call_depth: usize) {
+        self.max_call_depth = max_call_depth;
+    }
+
+    pub(crate) fn max_call_depth(&self) -> usize {
+        self.max_call_depth
+    }
+
+    pub(crate) fn call_depth_exceeded(&self) -> bool {
+        self.call_depth >= self.max_call_depth
+    }
+
+    /// Limits how many bytes of native stack evaluation can use before it
+    /// fails with
+    /// [`EvalError::StackExhausted`](crate::EvalError::StackExhausted)
+    /// instead of crashing the process. Raise it only when evaluating on a
+    /// thread with a larger stack than the default.
+    pub fn set_stack_limit(&mut self, stack_limit: usize) {
+        self.stack_limit = stack_limit;
+    }
+
+    /// Measures stack use from the current position unless evaluation is
+    /// already under way, returning the previous starting point so it can be
+    /// restored.
+    pub(crate) fn start_stack(&mut self) -> usize {
+        let stack_base = self.stack_base;
+        if stack_base == 0 {
+            self.stack_base = stack_position();
+        }
+        stack_base
+    }
+
+    pub(crate) fn restore_stack(&mut self, stack_base: usize) {
+        self.stack_base = stack_base;
+    }
+
+    /// Fails once evaluation has used more stack than the limit allows. Calls,
+    /// operators and indexing check this before recursing, since they are
+    /// what a program can nest without bound.
+    pub(crate) fn check_stack(&self, span: Span) -> Result<(), EvalError> {
+        if self.stack_base != 0 && self.stack_base.abs_diff(stack_position()) > self.stack_limit {
+            Err(EvalError::StackExhausted {
+                limit: self.stack_limit,
+                span,
+            })
+        } else {
+            Ok(())
         }
     }
 
     pub(crate) fn store_binding(&mut self, name: String, val: Val) {
-        self.bindings.insert(name, NamedInfo::Binding(val));
+        let mut scope = self.scope.borrow_mut();
+        let val = weaken(val, &scope);
+        scope.bindings.insert(name, val);
     }
 
+    /// Defines a function that captures this environment, so it can call
+    /// itself and anything else defined here.
     pub(crate) fn store_func(&mut self, name: String, params: Vec<String>, body: Stmt) {
-        self.bindings.insert(name, NamedInfo::Func { params, body });
+        let closure = Closure {
+            params,
+            body: Rc::new(body),
+            scope: self.capture(),
+        };
+        self.store_binding(name, Val::Func(Rc::new(closure)));
     }
 
-    pub(crate) fn get_binding(&self, name: &str) -> Result<Val, String> {
-        self.chain_lookup(name)
-            .and_then(NamedInfo::into_binding)
-            .ok_or_else(|| format!("binding with name '{}' does not exist", name,))
+    /// Makes a Rust function callable from scripts under `name`, taking
+    /// exactly `arity` arguments. Calls with any other number of arguments
+    /// fail before `func` runs, and an error returned by `func` becomes an
+    /// [`EvalError::Native`].
+    ///
+    /// Like any binding, it can be shadowed by a script definition of the
+    /// same name.
+    pub fn register_fn<E: fmt::Display>(
+        &mut self,
+        name: &str,
+        arity: usize,
+        func: impl Fn(&[Val]) -> Result<Val, E> + 'static,
+    ) {
+        let owned_name = name.to_string();
+        self.store_native(name, arity, move |args, span| {
+            func(args).map_err(|error| EvalError::Native {
+                name: owned_name.clone(),
+                message: error.to_string(),
+                span,
+            })
+        });
     }
 
-    pub(crate) fn get_func(&self, name: &str) -> Result<(Vec<String>, Stmt), String> {
-        self.chain_lookup(name)
-            .and_then(NamedInfo::into_func)
-            .ok_or_else(|| format!("function with name '{}' does not exist", name,))
+    pub(crate) fn store_native(
+        &mut self,
+        name: &str,
+        arity: usize,
+        func: impl Fn(&[Val], Span) -> Result<Val, EvalError> + 'static,
+    ) {
+        let native = NativeFunc {
+            name: name.to_string(),
+            arity,
+            func: Box::new(func),
+        };
+        self.store_binding(name.to_string(), Val::Native(Rc::new(native)));
     }
 
-    fn chain_lookup(&self, name: &str) -> Option<NamedInfo> {
-        self.bindings
-            .get(name)
-            .cloned()
-            .or_else(|| self.parent.and_then(|parent| parent.chain_lookup(name)))
+    /// Replaces the value of `name` in the nearest scope that defines it.
+    /// Returns `false` if no scope does.
+    pub(crate) fn update_binding(&self, name: &str, val: Val) -> bool {
+        self.scope.borrow_mut().chain_update(name, val)
+    }
+
+    /// Looks `name` up in this scope and the ones it was created from.
+    pub fn get_binding(&self, name: &str) -> Option<Val> {
+        self.scope.borrow().chain_lookup(name)
+    }
+
+    /// Defines `name` in this scope, as a `let` would, replacing any previous
+    /// value.
+    pub fn set_binding(&mut self, name: &str, val: impl Into<Val>) {
+        self.store_binding(name.to_string(), val.into());
+    }
+
+    /// Removes `name` from this scope, returning its value. Bindings of the
+    /// same name in enclosing scopes become visible again.
+    pub fn remove_binding(&mut self, name: &str) -> Option<Val> {
+        self.scope
+            .borrow_mut()
+            .bindings
+            .remove(name)
+            .map(strengthen)
+    }
+
+    /// Everything defined in this scope, sorted by name. Functions are
+    /// included, since they are bound like any other value.
+    pub fn bindings(&self) -> Vec<(String, Val)> {
+        let mut bindings: Vec<_> = self
+            .scope
+            .borrow()
+            .bindings
+            .iter()
+            .map(|(name, val)| (name.clone(), strengthen(val.clone())))
+            .collect();
+        bindings.sort_by(|(a, _), (b, _)| a.cmp(b));
+        bindings
+    }
+
+    /// The names of the functions defined in this scope, sorted.
+    pub fn functions(&self) -> Vec<String> {
+        self.bindings()
+            .into_iter()
+            .filter(|(_, val)| matches!(val, Val::Func(_) | Val::Native(_)))
+            .map(|(name, _)| name)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_and_get_binding() {
+        let mut env = Env::default();
+        env.set_binding("ratio", 0.5);
+        env.set_binding("name", "eldiro");
+
+        assert_eq!(env.get_binding("ratio"), Some(Val::Float(0.5)));
+        assert_eq!(
+            env.get_binding("name"),
+            Some(Val::Str("eldiro".to_string()))
+        );
+        assert_eq!(env.get_binding("missing"), None);
+    }
+
+    #[test]
+    fn binding_set_by_host_visible_to_script() {
+        let mut env = Env::default();
+        env.set_binding("limit", 10);
+
+        let result = crate::parse("limit * 2").unwrap().eval(&mut env);
+        assert_eq!(result, Ok(Val::from(20)));
+    }
+
+    #[test]
+    fn remove_binding_uncovers_enclosing_one() {
+        let mut env = Env::default();
+        env.set_binding("x", 1);
+        let mut child = env.create_child();
+        child.set_binding("x", 2);
+
+        assert_eq!(child.remove_binding("x"), Some(Val::from(2)));
+        assert_eq!(child.get_binding("x"), Some(Val::from(1)));
+        assert_eq!(child.remove_binding("x"), None);
+    }
+
+    #[test]
+    fn enumerate_bindings_and_functions() {
+        let mut env = Env::default();
+        env.set_binding("b", true);
+        env.set_binding("a", Val::Unit);
+        crate::eval_in(&mut env, "fn twice x => x * 2").unwrap();
+
+        let names: Vec<_> = env.bindings().into_iter().map(|(name, _)| name).collect();
+        assert_eq!(names, ["a", "b", "twice"]);
+        assert_eq!(env.functions(), ["twice"]);
+    }
+
+    #[test]
+    fn dropping_env_frees_scope_defining_func() {
+        let mut env = Env::default();
+        crate::eval_in(
+            &mut env,
+            "fn count n => if n == 0 { 0 } else { count (n - 1) }",
+        )
+        .unwrap();
+        crate::eval_in(&mut env, "count 3").unwrap();
+
+        let scope = Rc::downgrade(&env.scope);
+        drop(env);
+        assert!(scope.upgrade().is_none());
+    }
+
+    #[test]
+    fn calls_defining_inner_funcs_are_freed() {
+        let mut env = Env::default();
+        crate::eval_in(&mut env, "fn outer => {\n  fn inner => 1\n  inner\n}").unwrap();
+        let references = Rc::strong_count(&env.scope);
+
+        for _ in 0..3 {
+            crate::eval_in(&mut env, "outer").unwrap();
+        }
+        assert_eq!(Rc::strong_count(&env.scope), references);
+    }
+
+    #[test]
+    fn inner_func_outlives_defining_call() {
+        let mut env = Env::default();
+        crate::eval_in(&mut env, "fn adder n => {\n  fn add x => x + n\n  add\n}").unwrap();
+        crate::eval_in(&mut env, "let addTwo = adder 2").unwrap();
+
+        assert_eq!(crate::eval_in(&mut env, "addTwo 3"), Ok(Val::from(5)));
+    }
+
+    #[test]
+    fn dropping_env_frees_scope_binding_lambdas() {
+        let mut env = Env::default();
+        crate::eval_in(&mut env, "let f = |x| x + 1").unwrap();
+        crate::eval_in(&mut env, "let fs = [f, |x| f x]").unwrap();
+        crate::eval_in(&mut env, "let m = {\"g\": |x| x}").unwrap();
+        crate::eval_in(&mut env, "f = |x| f").unwrap();
+        crate::eval_in(&mut env, "let g = {\n  let n = 0\n  |x| x + n\n}").unwrap();
+        crate::eval_in(&mut env, "fn adder n => |x| x + n\nlet addTwo = adder 2").unwrap();
+        crate::eval_in(
+            &mut env,
+            "fn make n => {\n  fn add x => x + n\n  add\n}\nlet addThree = make 3",
+        )
+        .unwrap();
+
+        let host = Rc::new(());
+        let captured = Rc::clone(&host);
+        env.register_fn("probe", 0, move |_| {
+            let _ = &captured;
+            Ok::<_, String>(Val::Unit)
+        });
+
+        let scope = Rc::downgrade(&env.scope);
+        let prelude = Rc::downgrade(env.scope.borrow().parent.as_ref().unwrap());
+        drop(env);
+        assert!(scope.upgrade().is_none());
+        assert!(prelude.upgrade().is_none());
+        assert_eq!(Rc::strong_count(&host), 1);
+    }
+
+    #[test]
+    fn freed_scopes_are_forgotten() {
+        let mut env = Env::default();
+        crate::eval_in(
+            &mut env,
+            "fn count n => if n == 0 { 0 } else { count (n - 1) }",
+        )
+        .unwrap();
+
+        for _ in 0..100 {
+            crate::eval_in(&mut env, "count 10").unwrap();
+        }
+        assert!(env.scopes.scopes.borrow().len() < 100);
+    }
+
+    #[test]
+    fn calls_binding_lambdas_are_freed() {
+        let mut env = Env::default();
+        crate::eval_in(
+            &mut env,
+            "fn make => {\n  let f = |x| x * 2\n  let fs = [f]\n  fs[0]\n}",
+        )
+        .unwrap();
+        let references = Rc::strong_count(&env.scope);
+
+        for _ in 0..3 {
+            crate::eval_in(&mut env, "make").unwrap();
+        }
+        assert_eq!(Rc::strong_count(&env.scope), references);
+        assert_eq!(crate::eval_in(&mut env, "(make) 4"), Ok(Val::from(8)));
+    }
+
+    #[test]
+    fn default_env_keeps_builtin_functions_out_of_listings() {
+        let env = Env::default();
+
+        assert!(env.get_binding("len").is_some());
+        assert_eq!(env.functions(), Vec::<String>::new());
+        assert_eq!(env.bindings(), Vec::new());
     }
 }
@@ -0,0 +1,417 @@
+use std::fmt;
+
+use crate::int::Int;
+use crate::program::Program;
+use crate::utils::Input;
+
+/// A range of byte offsets into the parsed source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    /// Which source the offsets are into, so that an error raised by code
+    /// from an earlier [`parse`](crate::parse) is not rendered against the
+    /// wrong text.
+    pub(crate) source_id: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Self {
+            start,
+            end,
+            source_id: 0,
+        }
+    }
+}
+
+/// The furthest point any parser reached before failing, together with
+/// everything that would have been accepted there.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub span: Span,
+    pub line: usize,
+    pub column: usize,
+    pub expected: Vec<String>,
+}
+
+impl ParseError {
+    /// An error at the start of `s`, spanning the character found there.
+    pub(crate) fn new(s: Input, expected: impl Into<String>) -> Self {
+        let found_len = s.as_str().chars().next().map_or(0, char::len_utf8);
+
+        Self {
+            span: s.span_to(s.split_at(found_len).0),
+            line: s.line(),
+            column: s.column(),
+            expected: vec![expected.into()],
+        }
+    }
+
+    /// Keeps whichever error got further into the input, combining the
+    /// expectations of both when they failed at the same position.
+    pub(crate) fn merge(self, other: Self) -> Self {
+        if other.span.start > self.span.start {
+            return other;
+        }
+        if other.span.start < self.span.start {
+            return self;
+        }
+
+        let mut merged = self;
+        for expected in other.expected {
+            if let Err(idx) = merged.expected.binary_search(&expected) {
+                merged.expected.insert(idx, expected);
+            }
+        }
+        merged
+    }
+
+    /// Renders the error along with the offending source line and a caret
+    /// underline.
+    pub fn render(&self, source: &str) -> String {
+        render_snippet(&self.expectation(), source, self.span)
+    }
+
+    fn expectation(&self) -> String {
+        match self.expected.split_last() {
+            None => "unexpected input".to_string(),
+            Some((last, [])) => format!("expected {}", last),
+            Some((last, init)) => format!("expected {} or {}", init.join(", "), last),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.expectation())
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Everything that can go wrong while evaluating a parsed program.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EvalError {
+    UnboundName {
+        name: String,
+        span: Span,
+    },
+    NotAFunction {
+        name: String,
+        span: Span,
+    },
+    ArityMismatch {
+        name: String,
+        expected: usize,
+        got: usize,
+        span: Span,
+    },
+    TypeMismatch {
+        expected: &'static str,
+        found: &'static str,
+        span: Span,
+    },
+    DivisionByZero {
+        span: Span,
+    },
+    IndexOutOfBounds {
+        index: Int,
+        len: usize,
+        span: Span,
+    },
+    MissingKey {
+        key: String,
+        span: Span,
+    },
+    Overflow {
+        span: Span,
+    },
+    OutsideLoop {
+        keyword: &'static str,
+        span: Span,
+    },
+    /// Returned by a function registered with
+    /// [`Env::register_fn`](crate::Env::register_fn).
+    Native {
+        name: String,
+        message: String,
+        span: Span,
+    },
+    StackOverflow {
+        limit: usize,
+        span: Span,
+    },
+    /// Evaluation nested deeper than
+    /// [`Env::set_stack_limit`](crate::Env::set_stack_limit) allows.
+    StackExhausted {
+        limit: usize,
+        span: Span,
+    },
+}
+
+impl EvalError {
+    /// The part of the source whose evaluation failed.
+    pub fn span(&self) -> Span {
+        match self {
+            Self::UnboundName { span, .. }
+            | Self::NotAFunction { span, .. }
+            | Self::ArityMismatch { span, .. }
+            | Self::TypeMismatch { span, .. }
+            | Self::DivisionByZero { span }
+            | Self::IndexOutOfBounds { span, .. }
+            | Self::MissingKey { span, .. }
+            | Self::Overflow { span }
+            | Self::OutsideLoop { span, .. }
+            | Self::Native { span, .. }
+            | Self::StackOverflow { span, .. }
+            | Self::StackExhausted { span, .. } => *span,
+        }
+    }
+
+    /// Renders the error like [`ParseError::render`] when it was raised by
+    /// code from `program`. Code from other programs, such as a function
+    /// defined by an earlier one, only gets the message.
+    pub fn render(&self, program: &Program) -> String {
+        let span = self.span();
+        if span.source_id == program.source_id() {
+            render_snippet(&self.to_string(), program.source(), span)
+        } else {
+            format!("error: {}", self)
+        }
+    }
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnboundName { name, .. } => write!(f, "'{}' is not defined", name),
+            Self::NotAFunction { name, .. } => write!(f, "'{}' is not a function", name),
+            Self::ArityMismatch {
+                name,
+                expected,
+                got,
+                ..
+            } => write!(
+                f,
+                "'{}' expected {} parameters, got {}",
+                name, expected, got,
+            ),
+            Self::TypeMismatch {
+                expected, found, ..
+            } => write!(f, "expected {}, found {}", expected, found),
+            Self::DivisionByZero { .. } => write!(f, "division by zero"),
+            Self::IndexOutOfBounds { index, len, .. } => {
+                write!(f, "index {} is out of bounds for length {}", index, len)
+            }
+            Self::MissingKey { key, .. } => write!(f, "no key {:?} in map", key),
+            Self::Overflow { .. } => write!(f, "arithmetic overflow"),
+            Self::OutsideLoop { keyword, .. } => write!(f, "`{}` outside of a loop", keyword),
+            Self::Native { name, message, .. } => write!(f, "'{}' failed: {}", name, message),
+            Self::StackOverflow { limit, .. } => {
+                write!(f, "stack depth exceeded: more than {} nested calls", limit)
+            }
+            Self::StackExhausted { limit, .. } => {
+                write!(
+                    f,
+                    "stack exhausted: more than {} bytes of stack used",
+                    limit
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for EvalError {}
+
+/// Returned when converting a [`Val`](crate::Val) into a Rust type that does
+/// not match it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConversionError {
+    TypeMismatch {
+        expected: &'static str,
+        found: &'static str,
+    },
+    /// The value is a number, but the integer type cannot hold it.
+    OutOfRange { target: &'static str },
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TypeMismatch { expected, found } => {
+                write!(f, "expected {}, found {}", expected, found)
+            }
+            Self::OutOfRange { target } => write!(f, "number out of range for {}", target),
+        }
+    }
+}
+
+impl std::error::Error for ConversionError {}
+
+fn render_snippet(message: &str, source: &str, span: Span) -> String {
+    let start = floor_char_boundary(source, span.start);
+    let line_start = source[..start].rfind('\n').map_or(0, |idx| idx + 1);
+    let line = source[line_start..].lines().next().unwrap_or("");
+    let line_number = source[..line_start].matches('\n').count() + 1;
+
+    let line_number = line_number.to_string();
+    let gutter = " ".repeat(line_number.len());
+
+    let indent: String = source[line_start..start]
+        .chars()
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect();
+    let end = floor_char_boundary(source, span.end.min(line_start + line.len())).max(start);
+    let width = source[start..end].chars().count().max(1);
+
+    format!(
+        "error: {}\n{}--> {}:{}\n{} |\n{} | {}\n{} | {}{}",
+        message,
+        gutter,
+        line_number,
+        indent.chars().count() + 1,
+        gutter,
+        line_number,
+        line,
+        gutter,
+        indent,
+        "^".repeat(width),
+    )
+}
+
+/// The closest offset at or before `offset` that `source` can be sliced at.
+fn floor_char_boundary(source: &str, offset: usize) -> usize {
+    (0..=offset.min(source.len()))
+        .rev()
+        .find(|idx| source.is_char_boundary(*idx))
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error_at(start: usize, expected: &[&str]) -> ParseError {
+        ParseError {
+            span: Span::new(start, start + 1),
+            line: 1,
+            column: start + 1,
+            expected: expected.iter().map(|e| e.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn merge_keeps_furthest_error() {
+        assert_eq!(
+            error_at(1, &["`let`"]).merge(error_at(4, &["identifier"])),
+            error_at(4, &["identifier"]),
+        );
+        assert_eq!(
+            error_at(4, &["identifier"]).merge(error_at(1, &["`let`"])),
+            error_at(4, &["identifier"]),
+        );
+    }
+
+    #[test]
+    fn merge_combines_expectations_at_same_position() {
+        assert_eq!(
+            error_at(2, &["digits", "identifier"]).merge(error_at(2, &["`{`", "digits"])),
+            error_at(2, &["`{`", "digits", "identifier"]),
+        );
+    }
+
+    #[test]
+    fn display_error() {
+        assert_eq!(
+            error_at(2, &["`(`", "digits", "identifier"]).to_string(),
+            "1:3: expected `(`, digits or identifier",
+        );
+    }
+
+    #[test]
+    fn render_error_with_caret() {
+        let error = ParseError {
+            span: Span::new(14, 15),
+            line: 2,
+            column: 5,
+            expected: vec!["identifier".to_string()],
+        };
+
+        assert_eq!(
+            error.render("let a = 1\nlet 2 = 3"),
+            "error: expected identifier
+ --> 2:5
+  |
+2 | let 2 = 3
+  |     ^",
+        );
+    }
+
+    #[test]
+    fn display_eval_error() {
+        assert_eq!(
+            EvalError::ArityMismatch {
+                name: "add".to_string(),
+                expected: 2,
+                got: 1,
+                span: Span::new(0, 5),
+            }
+            .to_string(),
+            "'add' expected 2 parameters, got 1",
+        );
+    }
+
+    fn eval_error(program: &Program) -> EvalError {
+        program.eval(&mut crate::Env::default()).unwrap_err()
+    }
+
+    #[test]
+    fn render_eval_error_spanning_multiple_characters() {
+        let program = crate::parse("1 + foo").unwrap();
+
+        assert_eq!(
+            eval_error(&program).render(&program),
+            "error: 'foo' is not defined
+ --> 1:5
+  |
+1 | 1 + foo
+  |     ^^^",
+        );
+    }
+
+    #[test]
+    fn render_eval_error_from_other_program_without_snippet() {
+        let mut env = crate::Env::default();
+        crate::parse("fn f x => x / 0")
+            .unwrap()
+            .eval(&mut env)
+            .unwrap();
+
+        for call in ["f \"éééééééé\"", "f 1"] {
+            let program = crate::parse(call).unwrap();
+            let error = program.eval(&mut env).unwrap_err();
+
+            assert_eq!(error.render(&program), format!("error: {}", error));
+        }
+    }
+
+    #[test]
+    fn render_error_inside_multi_byte_character() {
+        let error = ParseError {
+            span: Span::new(2, 5),
+            line: 1,
+            column: 2,
+            expected: vec!["digits".to_string()],
+        };
+
+        assert_eq!(
+            error.render("\"éé\""),
+            "error: expected digits
+ --> 1:2
+  |
+1 | \"éé\"
+  |  ^^",
+        );
+    }
+}
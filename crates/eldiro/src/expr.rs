@@ -1,22 +1,100 @@
 pub(crate) use binding_usage::BindingUsage;
 pub(crate) use block::Block;
 pub(crate) use func_call::FuncCall;
+pub(crate) use if_else::IfElse;
+pub(crate) use lambda::Lambda;
+pub(crate) use list::{Index, List};
+pub(crate) use loops::{For, Loop, While};
+pub(crate) use map::Map;
 
-use crate::env::Env;
-use crate::utils;
-use crate::val::Val;
+use crate::closure::Tail;
+use crate::env::{Arithmetic, Env};
+use crate::error::{EvalError, ParseError, Span};
+use crate::int::{self, Int};
+use crate::signal::Signal;
+use crate::utils::{self, Input, OrParse};
+use crate::val::{Num, Val};
 
 mod binding_usage;
 mod block;
 mod func_call;
+mod if_else;
+mod lambda;
+mod list;
+mod loops;
+mod map;
 
 #[derive(Debug, PartialEq, Clone)]
-pub(crate) struct Number(pub(crate) i32);
+pub(crate) struct Number(pub(crate) Int);
+
+#[cfg(test)]
+impl From<i32> for Number {
+    fn from(n: i32) -> Self {
+        Self(Int::from(n))
+    }
+}
 
 impl Number {
-    fn new(s: &str) -> Result<(&str, Self), String> {
-        let (s, number) = utils::extract_digits(s)?;
-        Ok((s, Self(number.parse().unwrap())))
+    fn new(s: Input) -> Result<(Input, Self), ParseError> {
+        Self::new_signed(s, "")
+    }
+
+    /// Parses the literal together with a `-` in front of it, so that the
+    /// most negative integer can be written even though its digits alone do
+    /// not fit.
+    fn new_negative(s: Input) -> Result<(Input, Self), ParseError> {
+        Self::new_signed(s, "-")
+    }
+
+    /// A literal that does not fit is reported from the digit where it stops
+    /// fitting, so that the error is not mistaken for a missing number.
+    fn new_signed<'a>(s: Input<'a>, sign: &str) -> Result<(Input<'a>, Self), ParseError> {
+        let (rest, digits) = utils::extract_number(s)?;
+        let parse = |len| format!("{}{}", sign, &digits[..len]).parse::<Int>();
+
+        match parse(digits.len()) {
+            Ok(number) => Ok((rest, Self(number))),
+            Err(_) => {
+                let fitting = (1..digits.len())
+                    .take_while(|len| parse(*len).is_ok())
+                    .last()
+                    .unwrap_or(0);
+                let (overflow, _) = s.split_at(fitting);
+
+                let expected = if sign.is_empty() {
+                    format!("a number no greater than {}", i64::MAX)
+                } else {
+                    format!("a number no less than {}", i64::MIN)
+                };
+                Err(ParseError {
+                    span: overflow.span_to(rest),
+                    ..ParseError::new(overflow, expected)
+                })
+            }
+        }
+    }
+}
+
+/// A number literal with a fraction, an exponent or both, such as `3.14` or
+/// `1e-9`. Literals without either are integers.
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) struct Float(pub(crate) f64);
+
+impl Float {
+    fn new(s: Input) -> Result<(Input, Self), ParseError> {
+        let (rest, literal) = utils::extract_number(s)?;
+
+        match literal.parse::<f64>() {
+            Ok(f) if f.is_finite() => Ok((rest, Self(f))),
+            _ => Err(ParseError {
+                span: s.span_to(rest),
+                ..ParseError::new(s, "a finite number")
+            }),
+        }
+    }
+
+    fn is_float_literal(literal: &str) -> bool {
+        literal.contains(['.', 'e', 'E'])
     }
 }
 
@@ -26,93 +104,440 @@ pub(crate) enum Op {
     Sub,
     Mul,
     Div,
+    Eq,
+    NotEq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+    And,
+    Or,
 }
 
 impl Op {
-    fn new(s: &str) -> Result<(&str, Self), String> {
+    fn new(s: Input) -> Result<(Input, Self), ParseError> {
         utils::tag("+", s)
             .map(|s| (s, Self::Add))
-            .or_else(|_| utils::tag("-", s).map(|s| (s, Self::Sub)))
-            .or_else(|_| utils::tag("*", s).map(|s| (s, Self::Mul)))
-            .or_else(|_| utils::tag("/", s).map(|s| (s, Self::Div)))
+            .or_parse(|| utils::tag("-", s).map(|s| (s, Self::Sub)))
+            .or_parse(|| utils::tag("*", s).map(|s| (s, Self::Mul)))
+            .or_parse(|| utils::tag("/", s).map(|s| (s, Self::Div)))
+            .or_parse(|| utils::tag("==", s).map(|s| (s, Self::Eq)))
+            .or_parse(|| utils::tag("!=", s).map(|s| (s, Self::NotEq)))
+            .or_parse(|| utils::tag("<=", s).map(|s| (s, Self::LtEq)))
+            .or_parse(|| utils::tag("<", s).map(|s| (s, Self::Lt)))
+            .or_parse(|| utils::tag(">=", s).map(|s| (s, Self::GtEq)))
+            .or_parse(|| utils::tag(">", s).map(|s| (s, Self::Gt)))
+            .or_parse(|| utils::tag("&&", s).map(|s| (s, Self::And)))
+            .or_parse(|| utils::tag("||", s).map(|s| (s, Self::Or)))
+    }
+
+    /// Left and right binding powers; a higher left than right power makes
+    /// operators of the same precedence associate to the left.
+    fn binding_power(&self) -> (u8, u8) {
+        match self {
+            Self::Or => (1, 2),
+            Self::And => (3, 4),
+            Self::Eq | Self::NotEq | Self::Lt | Self::LtEq | Self::Gt | Self::GtEq => (5, 6),
+            Self::Add | Self::Sub => (7, 8),
+            Self::Mul | Self::Div => (9, 10),
+        }
+    }
+
+    /// Applies the operator to an evaluated left-hand side. The right-hand side
+    /// is only evaluated if `&&` or `||` cannot short-circuit. `+` joins two
+    /// strings as well as adding numbers.
+    ///
+    /// Integers are promoted to floats when mixed with them, both in
+    /// arithmetic and in comparisons.
+    pub(crate) fn eval(
+        &self,
+        lhs: Val,
+        rhs: impl FnOnce() -> Result<Val, Signal>,
+        arithmetic: Arithmetic,
+        span: Span,
+    ) -> Result<Val, Signal> {
+        match self {
+            Self::And | Self::Or => {
+                let lhs = lhs.expect_bool(span)?;
+                if lhs == (*self == Self::Or) {
+                    return Ok(Val::Bool(lhs));
+                }
+                Ok(Val::Bool(rhs()?.expect_bool(span)?))
+            }
+            Self::Eq | Self::NotEq => {
+                let rhs = rhs()?;
+                let equal = match (lhs.as_num(), rhs.as_num()) {
+                    (Some(lhs), Some(rhs)) => lhs == rhs,
+                    _ if lhs.type_name() != rhs.type_name() => {
+                        return Err(EvalError::TypeMismatch {
+                            expected: lhs.type_name(),
+                            found: rhs.type_name(),
+                            span,
+                        }
+                        .into());
+                    }
+                    _ => lhs == rhs,
+                };
+                Ok(Val::Bool(equal == (*self == Self::Eq)))
+            }
+            Self::Lt | Self::LtEq | Self::Gt | Self::GtEq => {
+                let lhs = lhs.expect_num(span)?;
+                let rhs = rhs()?.expect_num(span)?;
+
+                Ok(Val::Bool(match self {
+                    Self::Lt => lhs < rhs,
+                    Self::LtEq => lhs <= rhs,
+                    Self::Gt => lhs > rhs,
+                    _ => lhs >= rhs,
+                }))
+            }
+            Self::Add if matches!(lhs, Val::Str(_)) => {
+                let lhs = lhs.expect_str(span)?;
+                let rhs = rhs()?.expect_str(span)?;
+
+                Ok(Val::Str(lhs + &rhs))
+            }
+            Self::Add | Self::Sub | Self::Mul | Self::Div => {
+                let lhs = lhs.expect_num(span)?;
+                let rhs = rhs()?.expect_num(span)?;
+
+                let (lhs, rhs) = match (lhs, rhs) {
+                    (Num::Int(lhs), Num::Int(rhs)) => (lhs, rhs),
+                    (lhs, rhs) => {
+                        return Ok(Val::Float(self.apply_float(lhs.as_f64(), rhs.as_f64())))
+                    }
+                };
+
+                if *self == Self::Div && rhs == Int::from(0) {
+                    return Err(EvalError::DivisionByZero { span }.into());
+                }
+
+                int::apply(self, lhs, rhs, arithmetic)
+                    .map(Val::Number)
+                    .ok_or_else(|| EvalError::Overflow { span }.into())
+            }
+        }
+    }
+
+    /// Float arithmetic follows IEEE 754, so dividing by zero gives an
+    /// infinity or NaN instead of an error.
+    fn apply_float(&self, lhs: f64, rhs: f64) -> f64 {
+        match self {
+            Self::Add => lhs + rhs,
+            Self::Sub => lhs - rhs,
+            Self::Mul => lhs * rhs,
+            _ => lhs / rhs,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) enum UnaryOp {
+    Neg,
+    Not,
+}
+
+impl UnaryOp {
+    fn new(s: Input) -> Result<(Input, Self), ParseError> {
+        utils::tag("-", s)
+            .map(|s| (s, Self::Neg))
+            .or_parse(|| utils::tag("!", s).map(|s| (s, Self::Not)))
+    }
+
+    /// Prefix operators bind tighter than any binary operator.
+    fn binding_power(&self) -> u8 {
+        match self {
+            Self::Neg | Self::Not => 11,
+        }
     }
 }
 
 #[derive(Debug, PartialEq, Clone)]
 pub(crate) enum Expr {
     Number(Number),
+    Float(Float),
+    Bool(bool),
+    Str(String),
+    List(List),
+    Map(Map),
+    Index(Index),
     Operation {
         lhs: Box<Self>,
         rhs: Box<Self>,
         op: Op,
+        span: Span,
+    },
+    Unary {
+        operand: Box<Self>,
+        op: UnaryOp,
+        span: Span,
     },
+    Paren(Box<Self>),
     FuncCall(FuncCall),
     BindingUsage(BindingUsage),
     Block(Block),
+    IfElse(IfElse),
+    While(While),
+    Loop(Loop),
+    For(For),
+    Lambda(Lambda),
 }
 
 impl Expr {
-    pub(crate) fn new(s: &str) -> Result<(&str, Self), String> {
-        Self::new_operation(s).or_else(|_| Self::new_non_operation(s))
+    pub(crate) fn new(s: Input) -> Result<(Input, Self), ParseError> {
+        Self::new_with_binding_power(s, 0, true)
+    }
+
+    /// Parses the condition of an `if` or a `while`, where a `{` starts the body
+    /// rather than a block argument to a function call.
+    fn new_condition(s: Input) -> Result<(Input, Self), ParseError> {
+        Self::new_with_binding_power(s, 0, false)
+    }
+
+    /// Precedence climbing: parses operands and keeps folding operators into
+    /// the left-hand side as long as they bind at least as tightly as `min_bp`.
+    ///
+    /// An operator has to start on the same line as its left operand, so a new
+    /// line always starts a new statement; the right operand may follow on the
+    /// next line.
+    ///
+    /// Every nested expression is parsed by a call to this, so it is where
+    /// the depth of nesting is limited.
+    fn new_with_binding_power(
+        s: Input,
+        min_bp: u8,
+        block_args: bool,
+    ) -> Result<(Input, Self), ParseError> {
+        let s = s.nested()?;
+        let start = s;
+        let (mut s, mut lhs) =
+            Self::new_unary(s, block_args).or_parse(|| Self::new_non_operation(s, block_args))?;
+
+        loop {
+            let (after_ws, _) = utils::extract_non_breaks(s);
+            let (after_op, op) = match Op::new(after_ws) {
+                Ok(op) => op,
+                Err(_) => break,
+            };
+
+            let (l_bp, r_bp) = op.binding_power();
+            if l_bp < min_bp {
+                break;
+            }
+
+            let (after_op, _) = utils::extract_whitespace(after_op);
+            let (new_s, rhs) = Self::new_with_binding_power(after_op, r_bp, block_args)?;
+
+            s = new_s;
+            lhs = Self::Operation {
+                lhs: Box::new(lhs),
+                rhs: Box::new(rhs),
+                op,
+                span: start.span_to(s),
+            };
+        }
+
+        Ok((s.unnested(), lhs))
+    }
+
+    /// Negating a number literal folds into the literal itself, so `-5` is
+    /// parsed as the number `-5` rather than as a negation.
+    fn new_unary(s: Input, block_args: bool) -> Result<(Input, Self), ParseError> {
+        let start = s;
+
+        let (s, op) = UnaryOp::new(s)?;
+        let (s, _) = utils::extract_whitespace(s);
+
+        if op == UnaryOp::Neg {
+            if let Some(negative) = Self::new_negative_number(s)? {
+                return Ok(negative);
+            }
+        }
+
+        let (s, operand) = Self::new_with_binding_power(s, op.binding_power(), block_args)?;
+
+        let expr = match (&op, operand) {
+            (UnaryOp::Neg, Self::Number(Number(n))) => {
+                match int::neg(int::duplicate(&n), Arithmetic::Checked) {
+                    Some(negated) => Self::Number(Number(negated)),
+                    None => Self::Unary {
+                        operand: Box::new(Self::Number(Number(n))),
+                        op,
+                        span: start.span_to(s),
+                    },
+                }
+            }
+            (UnaryOp::Neg, Self::Float(Float(f))) => Self::Float(Float(-f)),
+            (_, operand) => Self::Unary {
+                operand: Box::new(operand),
+                op,
+                span: start.span_to(s),
+            },
+        };
+
+        Ok((s, expr))
+    }
+
+    /// An integer literal that is not indexed into, parsed with its sign.
+    fn new_negative_number(s: Input) -> Result<Option<(Input, Self)>, ParseError> {
+        match utils::extract_number(s) {
+            Ok((_, literal)) if !Float::is_float_literal(literal) => {}
+            _ => return Ok(None),
+        }
+
+        let (rest, number) = Number::new_negative(s)?;
+        Ok(match Index::new_chain(s, rest, Self::Number(number)) {
+            Ok((rest, number @ Self::Number(_))) => Some((rest, number)),
+            _ => None,
+        })
     }
 
-    fn new_non_operation(s: &str) -> Result<(&str, Self), String> {
-        Self::new_number(s)
-            .or_else(|_| FuncCall::new(s).map(|(s, func_call)| (s, Self::FuncCall(func_call))))
-            .or_else(|_| {
+    fn new_non_operation(s: Input, block_args: bool) -> Result<(Input, Self), ParseError> {
+        IfElse::new(s)
+            .map(|(s, if_else)| (s, Self::IfElse(if_else)))
+            .or_parse(|| While::new(s).map(|(s, while_loop)| (s, Self::While(while_loop))))
+            .or_parse(|| Loop::new(s).map(|(s, loop_expr)| (s, Self::Loop(loop_expr))))
+            .or_parse(|| For::new(s).map(|(s, for_loop)| (s, Self::For(for_loop))))
+            .or_parse(|| Lambda::new(s).map(|(s, lambda)| (s, Self::Lambda(lambda))))
+            .or_parse(|| Self::new_call_or_argument(s, block_args))
+    }
+
+    /// A name, a parenthesized expression or an index followed by arguments on
+    /// the same line is a function call.
+    fn new_call_or_argument(s: Input, block_args: bool) -> Result<(Input, Self), ParseError> {
+        let (after_callee, callee) = Self::new_argument(s, true)?;
+
+        if !matches!(
+            callee,
+            Self::BindingUsage(_) | Self::Paren(_) | Self::Index(_)
+        ) {
+            return Ok((after_callee, callee));
+        }
+
+        match FuncCall::new_params(after_callee, block_args) {
+            Ok((rest, params)) => Ok((
+                rest,
+                Self::FuncCall(FuncCall {
+                    callee: Box::new(callee),
+                    params,
+                    span: s.span_to(rest),
+                }),
+            )),
+            Err(_) => Ok((after_callee, callee)),
+        }
+    }
+
+    /// Operands that can be passed to a function call without parentheses.
+    /// Function calls themselves are excluded, so `add x y` passes `x` and `y`
+    /// instead of calling `x` with `y`.
+    ///
+    /// When `block_args` is false a `{` is left alone, so that `if x { 1 }`
+    /// reads `{ 1 }` as the body of the `if` rather than as an argument to `x`.
+    fn new_argument(s: Input, block_args: bool) -> Result<(Input, Self), ParseError> {
+        let (rest, atom) = Self::new_atom(s, block_args)?;
+        Index::new_chain(s, rest, atom)
+    }
+
+    fn new_atom(s: Input, block_args: bool) -> Result<(Input, Self), ParseError> {
+        let result = Self::new_number(s)
+            .or_parse(|| Self::new_bool(s))
+            .or_parse(|| utils::extract_string(s).map(|(s, string)| (s, Self::Str(string))))
+            .or_parse(|| {
                 BindingUsage::new(s)
                     .map(|(s, binding_usage)| (s, Self::BindingUsage(binding_usage)))
             })
-            .or_else(|_| Block::new(s).map(|(s, block)| (s, Self::Block(block))))
-    }
+            .or_parse(|| Self::new_paren(s))
+            .or_parse(|| List::new(s).map(|(s, list)| (s, Self::List(list))));
 
-    fn new_number(s: &str) -> Result<(&str, Self), String> {
-        Number::new(s).map(|(s, number)| (s, Self::Number(number)))
+        if block_args {
+            result.or_parse(|| Block::new_or_map(s))
+        } else {
+            result
+        }
     }
 
-    fn new_operation(s: &str) -> Result<(&str, Self), String> {
-        let (s, lhs) = Self::new_non_operation(s)?;
+    fn new_paren(s: Input) -> Result<(Input, Self), ParseError> {
+        let s = utils::tag("(", s)?;
         let (s, _) = utils::extract_whitespace(s);
 
-        let (s, op) = Op::new(s)?;
+        let (s, expr) = Self::new(s)?;
+
         let (s, _) = utils::extract_whitespace(s);
+        let s = utils::tag(")", s)?;
 
-        let (s, rhs) = Self::new_non_operation(s)?;
+        Ok((s, Self::Paren(Box::new(expr))))
+    }
 
-        Ok((
-            s,
-            Self::Operation {
-                lhs: Box::new(lhs),
-                rhs: Box::new(rhs),
-                op,
-            },
-        ))
+    fn new_number(s: Input) -> Result<(Input, Self), ParseError> {
+        let (_, literal) = utils::extract_number(s)?;
+
+        if Float::is_float_literal(literal) {
+            Float::new(s).map(|(s, float)| (s, Self::Float(float)))
+        } else {
+            Number::new(s).map(|(s, number)| (s, Self::Number(number)))
+        }
     }
 
-    pub(crate) fn eval(&self, env: &Env) -> Result<Val, String> {
-        match self {
-            Self::Number(Number(n)) => Ok(Val::Number(*n)),
-            Self::Operation { lhs, rhs, op } => {
-                let lhs = lhs.eval(env)?;
-                let rhs = rhs.eval(env)?;
+    fn new_bool(s: Input) -> Result<(Input, Self), ParseError> {
+        utils::keyword("true", s)
+            .map(|s| (s, Self::Bool(true)))
+            .or_parse(|| utils::keyword("false", s).map(|s| (s, Self::Bool(false))))
+    }
 
-                let (lhs, rhs) = match (lhs,rhs) {
-                    (Val::Number(lhs), Val::Number(rhs)) => (lhs, rhs),
-                    _ => return Err("cannot evaluate operation whose left-hand side and right-hand side are not both numbers".to_string()),
-                };
+    /// Evaluates everything but a call in tail position, which is left to the
+    /// caller.
+    pub(crate) fn eval_tail(&self, env: &Env) -> Result<Tail, Signal> {
+        match self {
+            Self::Paren(expr) => expr.eval_tail(env),
+            Self::Block(block) => block.eval_tail(env),
+            Self::FuncCall(fn_call) => fn_call.eval_tail(env),
+            Self::IfElse(if_else) => if_else.eval_tail(env),
+            expr => expr.eval(env).map(Tail::Val),
+        }
+    }
 
-                let result = match op {
-                    Op::Add => lhs + rhs,
-                    Op::Sub => lhs - rhs,
-                    Op::Mul => lhs * rhs,
-                    Op::Div => lhs / rhs,
-                };
+    pub(crate) fn eval(&self, env: &Env) -> Result<Val, Signal> {
+        match self {
+            Self::Number(Number(n)) => Ok(Val::Number(int::duplicate(n))),
+            Self::Float(Float(f)) => Ok(Val::Float(*f)),
+            Self::Bool(b) => Ok(Val::Bool(*b)),
+            Self::Str(s) => Ok(Val::Str(s.clone())),
+            Self::List(list) => list.eval(env),
+            Self::Map(map) => map.eval(env),
+            Self::Index(index) => index.eval(env),
+            Self::Operation { lhs, rhs, op, span } => {
+                env.check_stack(*span)?;
+                let lhs = lhs.eval(env)?;
+                op.eval(lhs, || rhs.eval(env), env.arithmetic(), *span)
+            }
+            Self::Unary { operand, op, span } => {
+                env.check_stack(*span)?;
+                match op {
+                    UnaryOp::Neg => {
+                        let n = match operand.eval(env)?.expect_num(*span)? {
+                            Num::Int(n) => n,
+                            Num::Float(f) => return Ok(Val::Float(-f)),
+                        };
 
-                Ok(Val::Number(result))
+                        int::neg(n, env.arithmetic())
+                            .map(Val::Number)
+                            .ok_or_else(|| EvalError::Overflow { span: *span }.into())
+                    }
+                    UnaryOp::Not => {
+                        let b = operand.eval(env)?.expect_bool(*span)?;
+                        Ok(Val::Bool(!b))
+                    }
+                }
             }
+            Self::Paren(expr) => expr.eval(env),
             Self::BindingUsage(binding_usage) => binding_usage.eval(env),
             Self::Block(block) => block.eval(env),
             Self::FuncCall(fn_call) => fn_call.eval(env),
+            Self::IfElse(if_else) => if_else.eval(env),
+            Self::While(while_loop) => while_loop.eval(env),
+            Self::Loop(loop_expr) => loop_expr.eval(env),
+            Self::For(for_loop) => for_loop.eval(env),
+            Self::Lambda(lambda) => Ok(lambda.eval(env)),
         }
     }
 }
@@ -120,78 +545,606 @@ impl Expr {
 #[cfg(test)]
 mod tests {
     use crate::env::Env;
+    use crate::error::Span;
     use crate::stmt::Stmt;
 
-    use super::*;
+    use super::*;
+
+    #[test]
+    fn parse_number() {
+        assert_eq!(
+            Number::new("123".into()),
+            Ok(("".into(), Number::from(123)))
+        );
+    }
+
+    #[cfg(not(feature = "bigint"))]
+    #[test]
+    fn parse_number_too_large() {
+        assert_eq!(
+            Number::new("99999999999999999999".into()),
+            Err(ParseError {
+                span: Span::new(18, 20),
+                line: 1,
+                column: 19,
+                expected: vec!["a number no greater than 9223372036854775807".to_string()],
+            }),
+        );
+    }
+
+    #[cfg(not(feature = "bigint"))]
+    #[test]
+    fn parse_number_too_large_in_expr() {
+        assert_eq!(
+            Expr::new("1 + 9223372036854775808".into()),
+            Err(ParseError {
+                span: Span::new(22, 23),
+                line: 1,
+                column: 23,
+                expected: vec!["a number no greater than 9223372036854775807".to_string()],
+            }),
+        );
+    }
+
+    #[cfg(not(feature = "bigint"))]
+    #[test]
+    fn parse_negative_number_too_small() {
+        assert_eq!(
+            Expr::new("-9223372036854775809".into()),
+            Err(ParseError {
+                span: Span::new(19, 20),
+                line: 1,
+                column: 20,
+                expected: vec!["a number no less than -9223372036854775808".to_string()],
+            }),
+        );
+    }
+
+    #[test]
+    fn parse_most_negative_number() {
+        let (_, expr) = Expr::new("-9223372036854775808".into()).unwrap();
+
+        assert_eq!(expr.eval(&Env::default()), Ok(Val::from(i64::MIN)));
+    }
+
+    #[test]
+    fn parse_negated_index_of_number_literal() {
+        assert!(matches!(
+            Expr::new("-5[0]".into()),
+            Ok((
+                _,
+                Expr::Unary {
+                    op: UnaryOp::Neg,
+                    ..
+                }
+            )),
+        ));
+    }
+
+    #[test]
+    fn parse_float() {
+        assert_eq!(Float::new("2.75".into()), Ok(("".into(), Float(2.75))));
+    }
+
+    #[test]
+    fn parse_float_with_exponent() {
+        assert_eq!(Float::new("1e-9".into()), Ok(("".into(), Float(1e-9))));
+        assert_eq!(Float::new("2.5E+3".into()), Ok(("".into(), Float(2500.0))));
+    }
+
+    #[test]
+    fn parse_integer_as_number_not_float() {
+        assert_eq!(
+            Expr::new("12".into()),
+            Ok(("".into(), Expr::Number(Number::from(12))))
+        );
+    }
+
+    #[test]
+    fn parse_dot_without_digits_not_part_of_float() {
+        assert_eq!(
+            Expr::new("1.".into()),
+            Ok((".".into(), Expr::Number(Number::from(1))))
+        );
+    }
+
+    #[test]
+    fn parse_negative_float() {
+        assert_eq!(
+            Expr::new("-0.5".into()),
+            Ok(("".into(), Expr::Float(Float(-0.5))))
+        );
+    }
+
+    #[test]
+    fn parse_float_too_large() {
+        assert_eq!(
+            Float::new("1e999".into()),
+            Err(ParseError {
+                span: Span::new(0, 5),
+                line: 1,
+                column: 1,
+                expected: vec!["a finite number".to_string()],
+            }),
+        );
+    }
+
+    #[test]
+    fn parse_add_op() {
+        assert_eq!(Op::new("+".into()), Ok(("".into(), Op::Add)));
+    }
+
+    #[test]
+    fn parse_sub_op() {
+        assert_eq!(Op::new("-".into()), Ok(("".into(), Op::Sub)));
+    }
+
+    #[test]
+    fn parse_mul_op() {
+        assert_eq!(Op::new("*".into()), Ok(("".into(), Op::Mul)));
+    }
+
+    #[test]
+    fn parse_div_op() {
+        assert_eq!(Op::new("/".into()), Ok(("".into(), Op::Div)));
+    }
+
+    #[test]
+    fn parse_comparison_ops() {
+        assert_eq!(Op::new("==".into()), Ok(("".into(), Op::Eq)));
+        assert_eq!(Op::new("!=".into()), Ok(("".into(), Op::NotEq)));
+        assert_eq!(Op::new("<".into()), Ok(("".into(), Op::Lt)));
+        assert_eq!(Op::new("<=".into()), Ok(("".into(), Op::LtEq)));
+        assert_eq!(Op::new(">".into()), Ok(("".into(), Op::Gt)));
+        assert_eq!(Op::new(">=".into()), Ok(("".into(), Op::GtEq)));
+    }
+
+    #[test]
+    fn parse_logical_ops() {
+        assert_eq!(Op::new("&&".into()), Ok(("".into(), Op::And)));
+        assert_eq!(Op::new("||".into()), Ok(("".into(), Op::Or)));
+    }
+
+    #[test]
+    fn parse_bools() {
+        assert_eq!(Expr::new("true".into()), Ok(("".into(), Expr::Bool(true))));
+        assert_eq!(
+            Expr::new("false".into()),
+            Ok(("".into(), Expr::Bool(false)))
+        );
+    }
+
+    #[test]
+    fn parse_str() {
+        assert_eq!(
+            Expr::new(r#""hello\tworld""#.into()),
+            Ok(("".into(), Expr::Str("hello\tworld".to_string()))),
+        );
+    }
+
+    #[test]
+    fn parse_func_call_with_str_argument() {
+        assert_eq!(
+            Expr::new(r#"greet "you""#.into()),
+            Ok((
+                "".into(),
+                Expr::FuncCall(FuncCall {
+                    callee: Box::new(Expr::BindingUsage(BindingUsage {
+                        name: "greet".to_string(),
+                        span: Span::new(0, 5),
+                    })),
+                    params: vec![Expr::Str("you".to_string())],
+                    span: Span::new(0, 11),
+                }),
+            )),
+        );
+    }
+
+    #[test]
+    fn parse_identifier_starting_with_bool_keyword() {
+        assert_eq!(
+            Expr::new("trueish".into()),
+            Ok((
+                "".into(),
+                Expr::BindingUsage(BindingUsage {
+                    name: "trueish".to_string(),
+                    span: Span::new(0, 7),
+                }),
+            )),
+        );
+    }
+
+    #[test]
+    fn parse_logical_and_comparison_precedence() {
+        assert_eq!(
+            Expr::new("1 + 1 < 3 || !b && false".into()),
+            Ok((
+                "".into(),
+                Expr::Operation {
+                    lhs: Box::new(Expr::Operation {
+                        lhs: Box::new(Expr::Operation {
+                            lhs: Box::new(Expr::Number(Number::from(1))),
+                            rhs: Box::new(Expr::Number(Number::from(1))),
+                            op: Op::Add,
+                            span: Span::new(0, 5),
+                        }),
+                        rhs: Box::new(Expr::Number(Number::from(3))),
+                        op: Op::Lt,
+                        span: Span::new(0, 9),
+                    }),
+                    rhs: Box::new(Expr::Operation {
+                        lhs: Box::new(Expr::Unary {
+                            operand: Box::new(Expr::BindingUsage(BindingUsage {
+                                name: "b".to_string(),
+                                span: Span::new(14, 15),
+                            })),
+                            op: UnaryOp::Not,
+                            span: Span::new(13, 15),
+                        }),
+                        rhs: Box::new(Expr::Bool(false)),
+                        op: Op::And,
+                        span: Span::new(13, 24),
+                    }),
+                    op: Op::Or,
+                    span: Span::new(0, 24),
+                },
+            )),
+        );
+    }
+
+    #[test]
+    fn parse_number_as_expr() {
+        assert_eq!(
+            Expr::new("456".into()),
+            Ok(("".into(), Expr::Number(Number::from(456))))
+        );
+    }
+
+    #[test]
+    fn parse_one_plus_two() {
+        assert_eq!(
+            Expr::new("1+2".into()),
+            Ok((
+                "".into(),
+                Expr::Operation {
+                    lhs: Box::new(Expr::Number(Number::from(1))),
+                    rhs: Box::new(Expr::Number(Number::from(2))),
+                    op: Op::Add,
+                    span: Span::new(0, 3),
+                }
+            )),
+        );
+    }
+
+    #[test]
+    fn parse_expr_with_whitespace() {
+        assert_eq!(
+            Expr::new("2 * 2".into()),
+            Ok((
+                "".into(),
+                Expr::Operation {
+                    lhs: Box::new(Expr::Number(Number::from(2))),
+                    rhs: Box::new(Expr::Number(Number::from(2))),
+                    op: Op::Mul,
+                    span: Span::new(0, 5),
+                },
+            )),
+        );
+    }
+
+    #[test]
+    fn parse_mul_before_add() {
+        assert_eq!(
+            Expr::new("1 + 2 * 3".into()),
+            Ok((
+                "".into(),
+                Expr::Operation {
+                    lhs: Box::new(Expr::Number(Number::from(1))),
+                    rhs: Box::new(Expr::Operation {
+                        lhs: Box::new(Expr::Number(Number::from(2))),
+                        rhs: Box::new(Expr::Number(Number::from(3))),
+                        op: Op::Mul,
+                        span: Span::new(4, 9),
+                    }),
+                    op: Op::Add,
+                    span: Span::new(0, 9),
+                },
+            )),
+        );
+    }
+
+    #[test]
+    fn parse_left_associative_sub() {
+        assert_eq!(
+            Expr::new("a - b - c".into()),
+            Ok((
+                "".into(),
+                Expr::Operation {
+                    lhs: Box::new(Expr::Operation {
+                        lhs: Box::new(Expr::BindingUsage(BindingUsage {
+                            name: "a".to_string(),
+                            span: Span::new(0, 1),
+                        })),
+                        rhs: Box::new(Expr::BindingUsage(BindingUsage {
+                            name: "b".to_string(),
+                            span: Span::new(4, 5),
+                        })),
+                        op: Op::Sub,
+                        span: Span::new(0, 5),
+                    }),
+                    rhs: Box::new(Expr::BindingUsage(BindingUsage {
+                        name: "c".to_string(),
+                        span: Span::new(8, 9),
+                    })),
+                    op: Op::Sub,
+                    span: Span::new(0, 9),
+                },
+            )),
+        );
+    }
+
+    #[test]
+    fn parse_long_operation_chain() {
+        assert_eq!(
+            Expr::new("8 / 4 / 2 + 1 * 2 - 3".into()),
+            Ok((
+                "".into(),
+                Expr::Operation {
+                    lhs: Box::new(Expr::Operation {
+                        lhs: Box::new(Expr::Operation {
+                            lhs: Box::new(Expr::Operation {
+                                lhs: Box::new(Expr::Number(Number::from(8))),
+                                rhs: Box::new(Expr::Number(Number::from(4))),
+                                op: Op::Div,
+                                span: Span::new(0, 5),
+                            }),
+                            rhs: Box::new(Expr::Number(Number::from(2))),
+                            op: Op::Div,
+                            span: Span::new(0, 9),
+                        }),
+                        rhs: Box::new(Expr::Operation {
+                            lhs: Box::new(Expr::Number(Number::from(1))),
+                            rhs: Box::new(Expr::Number(Number::from(2))),
+                            op: Op::Mul,
+                            span: Span::new(12, 17),
+                        }),
+                        op: Op::Add,
+                        span: Span::new(0, 17),
+                    }),
+                    rhs: Box::new(Expr::Number(Number::from(3))),
+                    op: Op::Sub,
+                    span: Span::new(0, 21),
+                },
+            )),
+        );
+    }
 
     #[test]
-    fn parse_number() {
-        assert_eq!(Number::new("123"), Ok(("", Number(123))));
+    fn parse_operation_does_not_continue_on_next_line() {
+        assert_eq!(
+            Expr::new("1\n+ 2".into()),
+            Ok(("\n+ 2".into(), Expr::Number(Number::from(1)))),
+        );
     }
 
     #[test]
-    fn parse_add_op() {
-        assert_eq!(Op::new("+"), Ok(("", Op::Add)));
+    fn parse_operation_with_rhs_on_next_line() {
+        assert_eq!(
+            Expr::new("1 +\n 2".into()),
+            Ok((
+                "".into(),
+                Expr::Operation {
+                    lhs: Box::new(Expr::Number(Number::from(1))),
+                    rhs: Box::new(Expr::Number(Number::from(2))),
+                    op: Op::Add,
+                    span: Span::new(0, 6),
+                },
+            )),
+        );
     }
 
     #[test]
-    fn parse_sub_op() {
-        assert_eq!(Op::new("-"), Ok(("", Op::Sub)));
+    fn parse_func_call_binds_tighter_than_operators() {
+        assert_eq!(
+            Expr::new("add x y * 2".into()),
+            Ok((
+                "".into(),
+                Expr::Operation {
+                    lhs: Box::new(Expr::FuncCall(FuncCall {
+                        callee: Box::new(Expr::BindingUsage(BindingUsage {
+                            name: "add".to_string(),
+                            span: Span::new(0, 3),
+                        })),
+                        params: vec![
+                            Expr::BindingUsage(BindingUsage {
+                                name: "x".to_string(),
+                                span: Span::new(4, 5),
+                            }),
+                            Expr::BindingUsage(BindingUsage {
+                                name: "y".to_string(),
+                                span: Span::new(6, 7),
+                            }),
+                        ],
+                        span: Span::new(0, 7),
+                    })),
+                    rhs: Box::new(Expr::Number(Number::from(2))),
+                    op: Op::Mul,
+                    span: Span::new(0, 11),
+                },
+            )),
+        );
     }
 
     #[test]
-    fn parse_mul_op() {
-        assert_eq!(Op::new("*"), Ok(("", Op::Mul)));
+    fn parse_negative_number() {
+        assert_eq!(
+            Expr::new("-5".into()),
+            Ok(("".into(), Expr::Number(Number::from(-5))))
+        );
     }
 
     #[test]
-    fn parse_div_op() {
-        assert_eq!(Op::new("/"), Ok(("", Op::Div)));
+    fn parse_negated_binding_usage() {
+        assert_eq!(
+            Expr::new("-a".into()),
+            Ok((
+                "".into(),
+                Expr::Unary {
+                    operand: Box::new(Expr::BindingUsage(BindingUsage {
+                        name: "a".to_string(),
+                        span: Span::new(1, 2),
+                    })),
+                    op: UnaryOp::Neg,
+                    span: Span::new(0, 2),
+                },
+            )),
+        );
     }
 
     #[test]
-    fn parse_number_as_expr() {
-        assert_eq!(Expr::new("456"), Ok(("", Expr::Number(Number(456)))));
+    fn parse_negation_before_mul() {
+        assert_eq!(
+            Expr::new("-a * 2".into()),
+            Ok((
+                "".into(),
+                Expr::Operation {
+                    lhs: Box::new(Expr::Unary {
+                        operand: Box::new(Expr::BindingUsage(BindingUsage {
+                            name: "a".to_string(),
+                            span: Span::new(1, 2),
+                        })),
+                        op: UnaryOp::Neg,
+                        span: Span::new(0, 2),
+                    }),
+                    rhs: Box::new(Expr::Number(Number::from(2))),
+                    op: Op::Mul,
+                    span: Span::new(0, 6),
+                },
+            )),
+        );
     }
 
     #[test]
-    fn parse_one_plus_two() {
+    fn parse_sub_of_negative_number() {
         assert_eq!(
-            Expr::new("1+2"),
+            Expr::new("1 - -1".into()),
             Ok((
-                "",
+                "".into(),
                 Expr::Operation {
-                    lhs: Box::new(Expr::Number(Number(1))),
-                    rhs: Box::new(Expr::Number(Number(2))),
-                    op: Op::Add,
-                }
+                    lhs: Box::new(Expr::Number(Number::from(1))),
+                    rhs: Box::new(Expr::Number(Number::from(-1))),
+                    op: Op::Sub,
+                    span: Span::new(0, 6),
+                },
             )),
         );
     }
 
     #[test]
-    fn parse_expr_with_whitespace() {
+    fn parse_paren() {
+        assert_eq!(
+            Expr::new("( 7 )".into()),
+            Ok((
+                "".into(),
+                Expr::Paren(Box::new(Expr::Number(Number::from(7))))
+            )),
+        );
+    }
+
+    #[test]
+    fn parse_paren_overriding_precedence() {
         assert_eq!(
-            Expr::new("2 * 2"),
+            Expr::new("(1 + 2) * 3".into()),
             Ok((
-                "",
+                "".into(),
                 Expr::Operation {
-                    lhs: Box::new(Expr::Number(Number(2))),
-                    rhs: Box::new(Expr::Number(Number(2))),
+                    lhs: Box::new(Expr::Paren(Box::new(Expr::Operation {
+                        lhs: Box::new(Expr::Number(Number::from(1))),
+                        rhs: Box::new(Expr::Number(Number::from(2))),
+                        op: Op::Add,
+                        span: Span::new(1, 6),
+                    }))),
+                    rhs: Box::new(Expr::Number(Number::from(3))),
                     op: Op::Mul,
+                    span: Span::new(0, 11),
+                },
+            )),
+        );
+    }
+
+    #[test]
+    fn parse_negated_paren() {
+        assert_eq!(
+            Expr::new("-(a + b)".into()),
+            Ok((
+                "".into(),
+                Expr::Unary {
+                    operand: Box::new(Expr::Paren(Box::new(Expr::Operation {
+                        lhs: Box::new(Expr::BindingUsage(BindingUsage {
+                            name: "a".to_string(),
+                            span: Span::new(2, 3),
+                        })),
+                        rhs: Box::new(Expr::BindingUsage(BindingUsage {
+                            name: "b".to_string(),
+                            span: Span::new(6, 7),
+                        })),
+                        op: Op::Add,
+                        span: Span::new(2, 7),
+                    }))),
+                    op: UnaryOp::Neg,
+                    span: Span::new(0, 8),
                 },
             )),
         );
     }
 
+    #[test]
+    fn parse_func_call_with_paren_argument() {
+        assert_eq!(
+            Expr::new("abs (-x)".into()),
+            Ok((
+                "".into(),
+                Expr::FuncCall(FuncCall {
+                    callee: Box::new(Expr::BindingUsage(BindingUsage {
+                        name: "abs".to_string(),
+                        span: Span::new(0, 3),
+                    })),
+                    params: vec![Expr::Paren(Box::new(Expr::Unary {
+                        operand: Box::new(Expr::BindingUsage(BindingUsage {
+                            name: "x".to_string(),
+                            span: Span::new(6, 7),
+                        })),
+                        op: UnaryOp::Neg,
+                        span: Span::new(5, 7),
+                    }))],
+                    span: Span::new(0, 8),
+                }),
+            )),
+        );
+    }
+
+    #[test]
+    fn parse_unclosed_paren() {
+        assert_eq!(
+            Expr::new("(1 + 2".into()),
+            Err(ParseError {
+                span: Span::new(6, 6),
+                line: 1,
+                column: 7,
+                expected: vec!["`)`".to_string()],
+            }),
+        );
+    }
+
     #[test]
     fn parse_binding_usage() {
         assert_eq!(
-            Expr::new("bar"),
+            Expr::new("bar".into()),
             Ok((
-                "",
+                "".into(),
                 Expr::BindingUsage(BindingUsage {
                     name: "bar".to_string(),
+                    span: Span::new(0, 3),
                 }),
             )),
         );
@@ -200,11 +1153,11 @@ mod tests {
     #[test]
     fn parse_block() {
         assert_eq!(
-            Expr::new("{ 200 }"),
+            Expr::new("{ 200 }".into()),
             Ok((
-                "",
+                "".into(),
                 Expr::Block(Block {
-                    stmts: vec![Stmt::Expr(Expr::Number(Number(200)))],
+                    stmts: vec![Stmt::Expr(Expr::Number(Number::from(200)))],
                 }),
             )),
         );
@@ -213,12 +1166,16 @@ mod tests {
     #[test]
     fn parse_func_call() {
         assert_eq!(
-            Expr::new("add 1 2"),
+            Expr::new("add 1 2".into()),
             Ok((
-                "",
+                "".into(),
                 Expr::FuncCall(FuncCall {
-                    callee: "add".to_string(),
-                    params: vec![Expr::Number(Number(1)), Expr::Number(Number(2))],
+                    callee: Box::new(Expr::BindingUsage(BindingUsage {
+                        name: "add".to_string(),
+                        span: Span::new(0, 3),
+                    })),
+                    params: vec![Expr::Number(Number::from(1)), Expr::Number(Number::from(2))],
+                    span: Span::new(0, 7),
                 }),
             )),
         );
@@ -228,12 +1185,13 @@ mod tests {
     fn eval_add() {
         assert_eq!(
             Expr::Operation {
-                lhs: Box::new(Expr::Number(Number(10))),
-                rhs: Box::new(Expr::Number(Number(10))),
+                lhs: Box::new(Expr::Number(Number::from(10))),
+                rhs: Box::new(Expr::Number(Number::from(10))),
                 op: Op::Add,
+                span: Span::default(),
             }
             .eval(&Env::default()),
-            Ok(Val::Number(20)),
+            Ok(Val::from(20)),
         );
     }
 
@@ -241,12 +1199,13 @@ mod tests {
     fn eval_sub() {
         assert_eq!(
             Expr::Operation {
-                lhs: Box::new(Expr::Number(Number(1))),
-                rhs: Box::new(Expr::Number(Number(5))),
+                lhs: Box::new(Expr::Number(Number::from(1))),
+                rhs: Box::new(Expr::Number(Number::from(5))),
                 op: Op::Sub,
+                span: Span::default(),
             }
             .eval(&Env::default()),
-            Ok(Val::Number(-4)),
+            Ok(Val::from(-4)),
         );
     }
 
@@ -254,12 +1213,13 @@ mod tests {
     fn eval_mul() {
         assert_eq!(
             Expr::Operation {
-                lhs: Box::new(Expr::Number(Number(5))),
-                rhs: Box::new(Expr::Number(Number(6))),
+                lhs: Box::new(Expr::Number(Number::from(5))),
+                rhs: Box::new(Expr::Number(Number::from(6))),
                 op: Op::Mul,
+                span: Span::default(),
             }
             .eval(&Env::default()),
-            Ok(Val::Number(30)),
+            Ok(Val::from(30)),
         );
     }
 
@@ -267,26 +1227,359 @@ mod tests {
     fn eval_div() {
         assert_eq!(
             Expr::Operation {
-                lhs: Box::new(Expr::Number(Number(200))),
-                rhs: Box::new(Expr::Number(Number(20))),
+                lhs: Box::new(Expr::Number(Number::from(200))),
+                rhs: Box::new(Expr::Number(Number::from(20))),
+                op: Op::Div,
+                span: Span::default(),
+            }
+            .eval(&Env::default()),
+            Ok(Val::from(10)),
+        );
+    }
+
+    #[test]
+    fn eval_operation_chain_respecting_precedence() {
+        let (_, expr) = Expr::new("2 + 3 * 4 - 10 / 5".into()).unwrap();
+        assert_eq!(expr.eval(&Env::default()), Ok(Val::from(12)));
+    }
+
+    #[test]
+    fn eval_neg() {
+        assert_eq!(
+            Expr::Unary {
+                operand: Box::new(Expr::Number(Number::from(3))),
+                op: UnaryOp::Neg,
+                span: Span::default(),
+            }
+            .eval(&Env::default()),
+            Ok(Val::from(-3)),
+        );
+    }
+
+    #[test]
+    fn eval_non_number_neg() {
+        assert_eq!(
+            Expr::Unary {
+                operand: Box::new(Expr::Block(Block { stmts: Vec::new() })),
+                op: UnaryOp::Neg,
+                span: Span::default(),
+            }
+            .eval(&Env::default()),
+            Err(EvalError::TypeMismatch {
+                expected: "number",
+                found: "unit",
+                span: Span::default(),
+            }
+            .into()),
+        );
+    }
+
+    #[test]
+    fn eval_paren() {
+        let (_, expr) = Expr::new("-(2 + 3) * (10 - 6)".into()).unwrap();
+        assert_eq!(expr.eval(&Env::default()), Ok(Val::from(-20)));
+    }
+
+    #[test]
+    fn eval_float_arithmetic() {
+        let (_, expr) = Expr::new("1.5 * 2.0 - 0.25".into()).unwrap();
+        assert_eq!(expr.eval(&Env::default()), Ok(Val::Float(2.75)));
+    }
+
+    #[test]
+    fn eval_mixed_arithmetic_promotes_to_float() {
+        let (_, expr) = Expr::new("1 / 4.0 + 3".into()).unwrap();
+        assert_eq!(expr.eval(&Env::default()), Ok(Val::Float(3.25)));
+    }
+
+    #[test]
+    fn eval_mixed_comparisons() {
+        for (source, expected) in [
+            ("1 == 1.0", true),
+            ("2 != 2.5", true),
+            ("2 < 2.5", true),
+            ("0.1 + 0.2 > 0.3", true),
+            ("3.0 <= 2", false),
+        ] {
+            let (_, expr) = Expr::new(source.into()).unwrap();
+            assert_eq!(expr.eval(&Env::default()), Ok(Val::Bool(expected)));
+        }
+    }
+
+    #[test]
+    fn eval_float_div_by_zero_is_infinite() {
+        let (_, expr) = Expr::new("1.0 / 0".into()).unwrap();
+        assert_eq!(expr.eval(&Env::default()), Ok(Val::Float(f64::INFINITY)));
+    }
+
+    #[test]
+    fn eval_neg_float() {
+        let (_, expr) = Expr::new("-(1.5)".into()).unwrap();
+        assert_eq!(expr.eval(&Env::default()), Ok(Val::Float(-1.5)));
+    }
+
+    #[test]
+    fn eval_div_by_zero() {
+        assert_eq!(
+            Expr::Operation {
+                lhs: Box::new(Expr::Number(Number::from(1))),
+                rhs: Box::new(Expr::Number(Number::from(0))),
                 op: Op::Div,
+                span: Span::new(0, 5),
             }
             .eval(&Env::default()),
-            Ok(Val::Number(10)),
+            Err(EvalError::DivisionByZero {
+                span: Span::new(0, 5),
+            }
+            .into()),
+        );
+    }
+
+    #[test]
+    fn eval_div_by_zero_is_an_error_in_every_mode() {
+        let (_, expr) = Expr::new("7 / (1 - 1)".into()).unwrap();
+
+        for arithmetic in [Arithmetic::Wrapping, Arithmetic::Saturating] {
+            let mut env = Env::default();
+            env.set_arithmetic(arithmetic);
+
+            assert_eq!(
+                expr.eval(&env),
+                Err(EvalError::DivisionByZero {
+                    span: Span::new(0, 11),
+                }
+                .into()),
+            );
+        }
+    }
+
+    #[cfg(not(feature = "bigint"))]
+    #[test]
+    fn eval_overflowing_add() {
+        let (_, expr) = Expr::new("9223372036854775807 + 1".into()).unwrap();
+
+        assert_eq!(
+            expr.eval(&Env::default()),
+            Err(EvalError::Overflow {
+                span: Span::new(0, 23),
+            }
+            .into()),
+        );
+    }
+
+    #[cfg(not(feature = "bigint"))]
+    #[test]
+    fn eval_overflowing_div() {
+        let (_, expr) = Expr::new("(-9223372036854775807 - 1) / -1".into()).unwrap();
+
+        assert_eq!(
+            expr.eval(&Env::default()),
+            Err(EvalError::Overflow {
+                span: Span::new(0, 31),
+            }
+            .into()),
+        );
+    }
+
+    #[cfg(not(feature = "bigint"))]
+    #[test]
+    fn eval_overflowing_neg() {
+        let (_, expr) = Expr::new("-(-9223372036854775807 - 1)".into()).unwrap();
+
+        assert_eq!(
+            expr.eval(&Env::default()),
+            Err(EvalError::Overflow {
+                span: Span::new(0, 27),
+            }
+            .into()),
+        );
+    }
+
+    #[cfg(not(feature = "bigint"))]
+    #[test]
+    fn eval_wrapping_arithmetic() {
+        let mut env = Env::default();
+        env.set_arithmetic(Arithmetic::Wrapping);
+
+        let (_, expr) = Expr::new("9223372036854775807 + 1".into()).unwrap();
+        assert_eq!(expr.eval(&env), Ok(Val::from(i64::MIN)));
+
+        let (_, expr) = Expr::new("(-9223372036854775807 - 1) / -1".into()).unwrap();
+        assert_eq!(expr.eval(&env), Ok(Val::from(i64::MIN)));
+    }
+
+    #[cfg(not(feature = "bigint"))]
+    #[test]
+    fn eval_saturating_arithmetic() {
+        let mut env = Env::default();
+        env.set_arithmetic(Arithmetic::Saturating);
+
+        let (_, expr) = Expr::new("9223372036854775807 * 2".into()).unwrap();
+        assert_eq!(expr.eval(&env), Ok(Val::from(i64::MAX)));
+
+        let (_, expr) = Expr::new("-(-9223372036854775807 - 1)".into()).unwrap();
+        assert_eq!(expr.eval(&env), Ok(Val::from(i64::MAX)));
+    }
+
+    #[cfg(not(feature = "bigint"))]
+    #[test]
+    fn eval_arithmetic_mode_inherited_by_blocks() {
+        let mut env = Env::default();
+        env.set_arithmetic(Arithmetic::Wrapping);
+
+        let (_, expr) = Expr::new("{ 9223372036854775807 + 2 }".into()).unwrap();
+        assert_eq!(expr.eval(&env), Ok(Val::from(i64::MIN + 1)));
+    }
+
+    #[test]
+    fn eval_comparisons() {
+        for (source, expected) in [
+            ("1 == 1", true),
+            ("1 != 1", false),
+            ("true == false", false),
+            ("true != false", true),
+            ("1 < 2", true),
+            ("2 <= 2", true),
+            ("1 > 2", false),
+            ("3 >= 4", false),
+        ] {
+            let (_, expr) = Expr::new(source.into()).unwrap();
+            assert_eq!(expr.eval(&Env::default()), Ok(Val::Bool(expected)));
+        }
+    }
+
+    #[test]
+    fn eval_logical_ops() {
+        for (source, expected) in [
+            ("true && false", false),
+            ("true && true", true),
+            ("false || true", true),
+            ("false || false", false),
+            ("!false", true),
+            ("!(1 < 2)", false),
+        ] {
+            let (_, expr) = Expr::new(source.into()).unwrap();
+            assert_eq!(expr.eval(&Env::default()), Ok(Val::Bool(expected)));
+        }
+    }
+
+    #[test]
+    fn eval_logical_ops_short_circuit() {
+        let (_, expr) = Expr::new("false && undefined".into()).unwrap();
+        assert_eq!(expr.eval(&Env::default()), Ok(Val::Bool(false)));
+
+        let (_, expr) = Expr::new("true || undefined".into()).unwrap();
+        assert_eq!(expr.eval(&Env::default()), Ok(Val::Bool(true)));
+    }
+
+    #[test]
+    fn eval_str_concatenation() {
+        let (_, expr) = Expr::new(r#""foo" + "bar" + "\u{21}""#.into()).unwrap();
+        assert_eq!(
+            expr.eval(&Env::default()),
+            Ok(Val::Str("foobar!".to_string())),
+        );
+    }
+
+    #[test]
+    fn eval_str_concatenation_with_number() {
+        let (_, expr) = Expr::new(r#""n = " + 1"#.into()).unwrap();
+
+        assert_eq!(
+            expr.eval(&Env::default()),
+            Err(EvalError::TypeMismatch {
+                expected: "string",
+                found: "number",
+                span: Span::new(0, 10),
+            }
+            .into()),
+        );
+    }
+
+    #[test]
+    fn eval_str_equality() {
+        for (source, expected) in [
+            (r#""a" == "a""#, true),
+            (r#""a" == "b""#, false),
+            (r#""a" != "b""#, true),
+        ] {
+            let (_, expr) = Expr::new(source.into()).unwrap();
+            assert_eq!(expr.eval(&Env::default()), Ok(Val::Bool(expected)));
+        }
+    }
+
+    #[test]
+    fn eval_equality_of_mixed_types() {
+        let (_, expr) = Expr::new("1 == true".into()).unwrap();
+
+        assert_eq!(
+            expr.eval(&Env::default()),
+            Err(EvalError::TypeMismatch {
+                expected: "number",
+                found: "bool",
+                span: Span::new(0, 9),
+            }
+            .into()),
+        );
+    }
+
+    #[test]
+    fn eval_logical_op_on_number() {
+        let (_, expr) = Expr::new("true && 1".into()).unwrap();
+
+        assert_eq!(
+            expr.eval(&Env::default()),
+            Err(EvalError::TypeMismatch {
+                expected: "bool",
+                found: "number",
+                span: Span::new(0, 9),
+            }
+            .into()),
+        );
+    }
+
+    #[test]
+    fn eval_arithmetic_on_bool() {
+        let (_, expr) = Expr::new("1 + false".into()).unwrap();
+
+        assert_eq!(
+            expr.eval(&Env::default()),
+            Err(EvalError::TypeMismatch {
+                expected: "number",
+                found: "bool",
+                span: Span::new(0, 9),
+            }
+            .into()),
+        );
+    }
+
+    #[test]
+    fn eval_not_on_number() {
+        let (_, expr) = Expr::new("!1".into()).unwrap();
+
+        assert_eq!(
+            expr.eval(&Env::default()),
+            Err(EvalError::TypeMismatch {
+                expected: "bool",
+                found: "number",
+                span: Span::new(0, 2),
+            }
+            .into()),
         );
     }
 
     #[test]
     fn eval_binding_usage() {
         let mut env = Env::default();
-        env.store_binding("ten".to_string(), Val::Number(10));
+        env.store_binding("ten".to_string(), Val::from(10));
 
         assert_eq!(
             Expr::BindingUsage(BindingUsage {
                 name: "ten".to_string(),
+                span: Span::default(),
             })
             .eval(&env),
-            Ok(Val::Number(10)),
+            Ok(Val::from(10)),
         );
     }
 
@@ -294,10 +1587,10 @@ mod tests {
     fn eval_block() {
         assert_eq!(
             Expr::Block(Block {
-                stmts: vec![Stmt::Expr(Expr::Number(Number(10)))],
+                stmts: vec![Stmt::Expr(Expr::Number(Number::from(10)))],
             })
             .eval(&Env::default()),
-            Ok(Val::Number(10)),
+            Ok(Val::from(10)),
         );
     }
 
@@ -305,11 +1598,32 @@ mod tests {
     fn eval_non_number_operation() {
         assert_eq!(
             Expr::Operation {
-                lhs: Box::new(Expr::Number(Number(10))),
+                lhs: Box::new(Expr::Number(Number::from(10))),
                 rhs: Box::new(Expr::Block(Block { stmts: Vec::new() })),
                 op: Op::Add,
-            }.eval(&Env::default()),
-            Err("cannot evaluate operation whose left-hand side and right-hand side are not both numbers".to_string()),
+                span: Span::new(0, 7),
+            }
+            .eval(&Env::default()),
+            Err(EvalError::TypeMismatch {
+                expected: "number",
+                found: "unit",
+                span: Span::new(0, 7),
+            }
+            .into()),
+        );
+    }
+
+    #[test]
+    fn eval_error_points_at_parsed_source() {
+        let (_, expr) = Expr::new("1 + foo".into()).unwrap();
+
+        assert_eq!(
+            expr.eval(&Env::default()),
+            Err(EvalError::UnboundName {
+                name: "foo".to_string(),
+                span: Span::new(4, 7),
+            }
+            .into()),
         );
     }
 
@@ -323,21 +1637,74 @@ mod tests {
             Stmt::Expr(Expr::Operation {
                 lhs: Box::new(Expr::BindingUsage(BindingUsage {
                     name: "z".to_string(),
+                    span: Span::default(),
                 })),
                 rhs: Box::new(Expr::BindingUsage(BindingUsage {
                     name: "y".to_string(),
+                    span: Span::default(),
                 })),
                 op: Op::Add,
+                span: Span::default(),
             }),
         );
 
         assert_eq!(
             Expr::FuncCall(FuncCall {
-                callee: "add".to_string(),
-                params: vec![Expr::Number(Number(2)), Expr::Number(Number(2))],
+                callee: Box::new(Expr::BindingUsage(BindingUsage {
+                    name: "add".to_string(),
+                    span: Span::default(),
+                })),
+                params: vec![Expr::Number(Number::from(2)), Expr::Number(Number::from(2))],
+                span: Span::default(),
             })
             .eval(&env),
-            Ok(Val::Number(4)),
+            Ok(Val::from(4)),
         );
     }
+
+    /// Parses `source` on a thread with the 2 MiB stack Rust gives spawned
+    /// threads by default.
+    fn parse_on_small_stack(source: String) -> Result<(), ParseError> {
+        std::thread::Builder::new()
+            .stack_size(2 * 1024 * 1024)
+            .spawn(move || crate::parse(&source).map(drop))
+            .unwrap()
+            .join()
+            .unwrap()
+    }
+
+    #[test]
+    fn parse_nesting_up_to_limit() {
+        let depth = Input::MAX_DEPTH - 1;
+
+        for (open, close) in [
+            ("(", ")"),
+            ("[", "]"),
+            ("{", "}"),
+            ("{\"a\": ", "}"),
+            ("-", ""),
+        ] {
+            let source = format!("{}x{}", open.repeat(depth), close.repeat(depth));
+            assert_eq!(parse_on_small_stack(source), Ok(()), "nested {}", open);
+        }
+    }
+
+    #[test]
+    fn parse_too_deeply_nested_code() {
+        let limit = format!("at most {} levels of nesting", Input::MAX_DEPTH);
+
+        for (open, close, depth) in [
+            ("(", ")", 1_000),
+            ("[", "]", 1_000),
+            ("{", "}", 1_000),
+            ("if x { ", " }", 1_000),
+            ("|x| ", "", 1_000),
+            ("-", "", 100_000),
+        ] {
+            let source = format!("{}x{}", open.repeat(depth), close.repeat(depth));
+            let error = parse_on_small_stack(source).unwrap_err();
+
+            assert!(error.expected.contains(&limit), "nested {}", open);
+        }
+    }
 }
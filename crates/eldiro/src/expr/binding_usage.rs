@@ -1,36 +1,42 @@
 use crate::env::Env;
-use crate::expr::FuncCall;
-use crate::utils;
+use crate::error::{EvalError, ParseError, Span};
+use crate::signal::Signal;
+use crate::utils::{self, Input};
 use crate::val::Val;
 
 #[derive(Debug, PartialEq, Clone)]
 pub(crate) struct BindingUsage {
     pub(crate) name: String,
+    pub(crate) span: Span,
 }
 
 impl BindingUsage {
-    pub(super) fn new(s: &str) -> Result<(&str, Self), String> {
-        let (s, name) = utils::extract_ident(s)?;
+    pub(super) fn new(s: Input) -> Result<(Input, Self), ParseError> {
+        let (rest, name) = utils::extract_ident(s)?;
         Ok((
-            s,
+            rest,
             Self {
                 name: name.to_string(),
+                span: s.span_to(rest),
             },
         ))
     }
 
-    pub(super) fn eval(&self, env: &Env) -> Result<Val, String> {
-        env.get_binding(&self.name).or_else(|error_msg| {
-            if env.get_func(&self.name).is_ok() {
-                FuncCall {
-                    callee: self.name.clone(),
-                    params: Vec::new(),
-                }
-                .eval(env)
-            } else {
-                Err(error_msg)
+    /// Naming a function without parameters calls it, since there would be no
+    /// other way to do so.
+    pub(super) fn eval(&self, env: &Env) -> Result<Val, Signal> {
+        match env.get_binding(&self.name) {
+            Some(Val::Func(closure)) if closure.params.is_empty() => {
+                closure.call(Vec::new(), env, self.span)
             }
-        })
+            Some(Val::Native(native)) if native.arity == 0 => Ok(native.call(&[], self.span)?),
+            Some(val) => Ok(val),
+            None => Err(EvalError::UnboundName {
+                name: self.name.clone(),
+                span: self.span,
+            }
+            .into()),
+        }
     }
 }
 
@@ -42,11 +48,12 @@ mod tests {
     #[test]
     fn parse_binding_usage() {
         assert_eq!(
-            BindingUsage::new("abc"),
+            BindingUsage::new("abc".into()),
             Ok((
-                "",
+                "".into(),
                 BindingUsage {
                     name: "abc".to_string(),
+                    span: Span::new(0, 3),
                 },
             )),
         );
@@ -55,29 +62,31 @@ mod tests {
     #[test]
     fn eval_existing_binding_usage() {
         let mut env = Env::default();
-        env.store_binding("foo".to_string(), Val::Number(10));
+        env.store_binding("foo".to_string(), Val::from(10));
 
         assert_eq!(
             BindingUsage {
                 name: "foo".to_string(),
+                span: Span::default(),
             }
             .eval(&env),
-            Ok(Val::Number(10)),
+            Ok(Val::from(10)),
         );
     }
 
     #[test]
     fn eval_existing_binding_def_usage() {
         let mut env = Env::default();
-        let (_, bd) = BindingDef::new("let foo=10").unwrap();
+        let (_, bd) = BindingDef::new("let foo=10".into()).unwrap();
         let _ = bd.eval(&mut env);
 
         assert_eq!(
             BindingUsage {
                 name: "foo".to_string(),
+                span: Span::default(),
             }
             .eval(&env),
-            Ok(Val::Number(10)),
+            Ok(Val::from(10)),
         );
     }
 
@@ -88,9 +97,22 @@ mod tests {
         assert_eq!(
             BindingUsage {
                 name: "i_dont_exist".to_string(),
+                span: Span::new(4, 16),
             }
             .eval(&empty_env),
-            Err("binding with name 'i_dont_exist' does not exist".to_string()),
+            Err(EvalError::UnboundName {
+                name: "i_dont_exist".to_string(),
+                span: Span::new(4, 16),
+            }
+            .into()),
+        );
+    }
+
+    #[test]
+    fn eval_usage_of_func_without_params_calls_it() {
+        assert_eq!(
+            crate::eval("fn answer => 42\n[answer, (answer)]").map(|val| val.to_string()),
+            Ok("[42, 42]".to_string()),
         );
     }
 }
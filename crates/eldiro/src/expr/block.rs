@@ -1,29 +1,43 @@
+use crate::closure::Tail;
 use crate::env::Env;
+use crate::error::ParseError;
+use crate::signal::Signal;
 use crate::stmt::Stmt;
-use crate::utils;
+use crate::utils::{self, Input};
 use crate::val::Val;
 
+use super::{Expr, Map};
+
 #[derive(Debug, PartialEq, Clone)]
 pub(crate) struct Block {
     pub(crate) stmts: Vec<Stmt>,
 }
 
 impl Block {
-    pub(super) fn new(s: &str) -> Result<(&str, Self), String> {
+    pub(super) fn new(s: Input) -> Result<(Input, Self), ParseError> {
         let s = utils::tag("{", s)?;
-        let (s, _) = utils::extract_whitespace(s);
+        let (s, stmts) = utils::separated_until(Stmt::new, Some("}"), s)?;
 
-        let (s, stmts) = utils::sequence(Stmt::new, s, None)?;
+        Ok((s, Block { stmts }))
+    }
 
-        let (s, _) = utils::extract_whitespace(s);
-        let s = utils::tag("}", s)?;
+    /// Where a value is expected, a `{` followed by a key and a `:` starts a
+    /// map literal instead.
+    pub(super) fn new_or_map(s: Input) -> Result<(Input, Expr), ParseError> {
+        if Map::starts_at(s) {
+            Map::new(s).map(|(s, map)| (s, Expr::Map(map)))
+        } else {
+            Self::new(s).map(|(s, block)| (s, Expr::Block(block)))
+        }
+    }
 
-        Ok((s, Block { stmts }))
+    pub(super) fn eval(&self, env: &Env) -> Result<Val, Signal> {
+        self.eval_tail(env)?.finish(env)
     }
 
-    pub(super) fn eval(&self, env: &Env) -> Result<Val, String> {
+    pub(super) fn eval_tail(&self, env: &Env) -> Result<Tail, Signal> {
         if self.stmts.is_empty() {
-            return Ok(Val::Unit);
+            return Ok(Tail::Val(Val::Unit));
         }
 
         let mut child_env = env.create_child();
@@ -32,35 +46,42 @@ impl Block {
         for stmt in heads {
             stmt.eval(&mut child_env)?;
         }
-        tail.eval(&mut child_env)
+        tail.eval_tail(&mut child_env)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::binding_def::BindingDef;
+    use crate::error::Span;
 
-    use super::super::{BindingUsage, Expr, Number, Op};
+    use super::super::{BindingUsage, Number, Op};
     use super::*;
 
     #[test]
     fn parse_empty_block() {
-        assert_eq!(Block::new("{}"), Ok(("", Block { stmts: Vec::new() })));
+        assert_eq!(
+            Block::new("{}".into()),
+            Ok(("".into(), Block { stmts: Vec::new() }))
+        );
     }
 
     #[test]
     fn parse_empty_block_with_whitespace() {
-        assert_eq!(Block::new("{   }"), Ok(("", Block { stmts: Vec::new() })));
+        assert_eq!(
+            Block::new("{   }".into()),
+            Ok(("".into(), Block { stmts: Vec::new() }))
+        );
     }
 
     #[test]
     fn parse_block_with_one_stmt() {
         assert_eq!(
-            Block::new("{ 5 }"),
+            Block::new("{ 5 }".into()),
             Ok((
-                "",
+                "".into(),
                 Block {
-                    stmts: vec![Stmt::Expr(Expr::Number(Number(5)))],
+                    stmts: vec![Stmt::Expr(Expr::Number(Number::from(5)))],
                 },
             )),
         );
@@ -75,23 +96,26 @@ mod tests {
                     let b = a
                     b
                 }"
+                .into()
             ),
             Ok((
-                "",
+                "".into(),
                 Block {
                     stmts: vec![
                         Stmt::BindingDef(BindingDef {
                             name: "a".to_string(),
-                            val: Expr::Number(Number(10)),
+                            val: Expr::Number(Number::from(10)),
                         }),
                         Stmt::BindingDef(BindingDef {
                             name: "b".to_string(),
                             val: Expr::BindingUsage(BindingUsage {
-                                name: "a".to_string()
+                                name: "a".to_string(),
+                                span: Span::new(61, 62),
                             }),
                         }),
                         Stmt::Expr(Expr::BindingUsage(BindingUsage {
-                            name: "b".to_string()
+                            name: "b".to_string(),
+                            span: Span::new(83, 84),
                         })),
                     ],
                 },
@@ -99,6 +123,82 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_block_with_comments_tabs_and_crlf() {
+        assert_eq!(
+            Block::new("{ # start\r\n\tlet a = 1 // one\r\n\t/* a\n/* nested */ */ a\r\n}".into()),
+            Ok((
+                "".into(),
+                Block {
+                    stmts: vec![
+                        Stmt::BindingDef(BindingDef {
+                            name: "a".to_string(),
+                            val: Expr::Number(Number::from(1)),
+                        }),
+                        Stmt::Expr(Expr::BindingUsage(BindingUsage {
+                            name: "a".to_string(),
+                            span: Span::new(52, 53),
+                        })),
+                    ],
+                },
+            )),
+        );
+    }
+
+    #[test]
+    fn parse_block_with_stmts_separated_by_semicolons() {
+        let (_, block) = Block::new("{ let a = 2; a ;; a }".into()).unwrap();
+
+        assert_eq!(block.stmts.len(), 3);
+        assert_eq!(block.eval(&Env::default()), Ok(Val::from(2)));
+    }
+
+    #[test]
+    fn parse_block_without_separator() {
+        assert_eq!(
+            Block::new("{ let a = 1 let b = 2 }".into()),
+            Err(ParseError {
+                span: Span::new(12, 13),
+                line: 1,
+                column: 13,
+                expected: vec![
+                    "`;`".to_string(),
+                    "`}`".to_string(),
+                    "a newline".to_string(),
+                ],
+            }),
+        );
+    }
+
+    #[test]
+    fn parse_block_reports_furthest_error() {
+        assert_eq!(
+            Block::new("{ 1 + }".into()),
+            Err(ParseError {
+                span: Span::new(6, 7),
+                line: 1,
+                column: 7,
+                expected: vec![
+                    "`!`".to_string(),
+                    "`\"`".to_string(),
+                    "`(`".to_string(),
+                    "`-`".to_string(),
+                    "`[`".to_string(),
+                    "`false`".to_string(),
+                    "`for`".to_string(),
+                    "`if`".to_string(),
+                    "`loop`".to_string(),
+                    "`true`".to_string(),
+                    "`while`".to_string(),
+                    "`{`".to_string(),
+                    "`|`".to_string(),
+                    "identifier".to_string(),
+                    "number".to_string(),
+                ],
+            }),
+        );
+    }
+
     #[test]
     fn eval_empty_block() {
         assert_eq!(
@@ -111,10 +211,10 @@ mod tests {
     fn eval_block_with_one_expr() {
         assert_eq!(
             Block {
-                stmts: vec![Stmt::Expr(Expr::Number(Number(25)))],
+                stmts: vec![Stmt::Expr(Expr::Number(Number::from(25)))],
             }
             .eval(&Env::default()),
-            Ok(Val::Number(25)),
+            Ok(Val::from(25)),
         );
     }
 
@@ -125,15 +225,16 @@ mod tests {
                 stmts: vec![
                     Stmt::BindingDef(BindingDef {
                         name: "one".to_string(),
-                        val: Expr::Number(Number(1)),
+                        val: Expr::Number(Number::from(1)),
                     }),
                     Stmt::Expr(Expr::BindingUsage(BindingUsage {
                         name: "one".to_string(),
+                        span: Span::default(),
                     })),
                 ],
             }
             .eval(&Env::default()),
-            Ok(Val::Number(1)),
+            Ok(Val::from(1)),
         );
     }
 
@@ -144,15 +245,15 @@ mod tests {
                 stmts: vec![
                     Stmt::BindingDef(BindingDef {
                         name: "foo".to_string(),
-                        val: Expr::Number(Number(5)),
+                        val: Expr::Number(Number::from(5)),
                     }),
                     Stmt::BindingDef(BindingDef {
                         name: "bar".to_string(),
-                        val: Expr::Number(Number(4)),
+                        val: Expr::Number(Number::from(4)),
                     }),
                     Stmt::BindingDef(BindingDef {
                         name: "baz".to_string(),
-                        val: Expr::Number(Number(3)),
+                        val: Expr::Number(Number::from(3)),
                     }),
                 ],
             }
@@ -166,24 +267,25 @@ mod tests {
         assert_eq!(
             Block {
                 stmts: vec![
-                    Stmt::Expr(Expr::Number(Number(100))),
-                    Stmt::Expr(Expr::Number(Number(30))),
+                    Stmt::Expr(Expr::Number(Number::from(100))),
+                    Stmt::Expr(Expr::Number(Number::from(30))),
                     Stmt::Expr(Expr::Operation {
-                        lhs: Box::new(Expr::Number(Number(10))),
-                        rhs: Box::new(Expr::Number(Number(7))),
+                        lhs: Box::new(Expr::Number(Number::from(10))),
+                        rhs: Box::new(Expr::Number(Number::from(7))),
                         op: Op::Sub,
+                        span: Span::default(),
                     }),
                 ],
             }
             .eval(&Env::default()),
-            Ok(Val::Number(3)),
+            Ok(Val::from(3)),
         );
     }
 
     #[test]
     fn eval_block_using_bindings_from_parent_env() {
         let mut env = Env::default();
-        env.store_binding("foo".to_string(), Val::Number(2));
+        env.store_binding("foo".to_string(), Val::from(2));
 
         assert_eq!(
             Block {
@@ -192,15 +294,17 @@ mod tests {
                         name: "baz".to_string(),
                         val: Expr::BindingUsage(BindingUsage {
                             name: "foo".to_string(),
+                            span: Span::default(),
                         }),
                     }),
                     Stmt::Expr(Expr::BindingUsage(BindingUsage {
                         name: "baz".to_string(),
+                        span: Span::default(),
                     })),
                 ],
             }
             .eval(&env),
-            Ok(Val::Number(2)),
+            Ok(Val::from(2)),
         );
     }
 
@@ -211,21 +315,22 @@ mod tests {
                 stmts: vec![
                     Stmt::BindingDef(BindingDef {
                         name: "foo".to_string(),
-                        val: Expr::Number(Number(17)),
+                        val: Expr::Number(Number::from(17)),
                     }),
                     Stmt::Expr(Expr::Block(Block {
                         stmts: vec![Stmt::BindingDef(BindingDef {
                             name: "foo".to_string(),
-                            val: Expr::Number(Number(42)),
+                            val: Expr::Number(Number::from(42)),
                         }),]
                     })),
                     Stmt::Expr(Expr::BindingUsage(BindingUsage {
-                        name: "foo".to_string()
+                        name: "foo".to_string(),
+                        span: Span::default(),
                     }))
                 ]
             }
             .eval(&Env::default()),
-            Ok(Val::Number(17))
+            Ok(Val::from(17))
         );
     }
 }
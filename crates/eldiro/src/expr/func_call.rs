@@ -1,53 +1,110 @@
-use crate::{utils, Env, Val};
+use crate::closure::Tail;
+use crate::error::{EvalError, ParseError, Span};
+use crate::signal::Signal;
+use crate::utils::{self, Input};
+use crate::{Env, Val};
 
 use super::Expr;
 
 #[derive(Debug, Clone, PartialEq)]
 pub(crate) struct FuncCall {
-    pub(crate) callee: String,
+    pub(crate) callee: Box<Expr>,
     pub(crate) params: Vec<Expr>,
+    pub(crate) span: Span,
 }
 
 impl FuncCall {
-    pub(super) fn new(s: &str) -> Result<(&str, Self), String> {
-        let (s, callee) = utils::extract_ident(s)?;
+    /// Parses the arguments following a callee, the first of which has to be
+    /// on the same line.
+    pub(super) fn new_params(s: Input, block_args: bool) -> Result<(Input, Vec<Expr>), ParseError> {
         let (s, _) = utils::extract_non_breaks(s);
 
-        let (s, param) = Expr::new(s)?;
-        let (s, _) = utils::extract_non_breaks(s);
-        let (s, more_params) =
-            utils::sequence(Expr::new, s, Some(Box::new(utils::extract_non_breaks)))?;
+        let (s, param) = Expr::new_argument(s, block_args)?;
+        let (s, more_params) = utils::sequence(
+            |s| Expr::new_argument(s, block_args),
+            s,
+            Some(Box::new(utils::extract_non_breaks)),
+        )?;
 
         let mut params: Vec<Expr> = vec![param];
         params.extend(more_params);
 
-        Ok((
-            s,
-            Self {
-                callee: callee.to_string(),
-                params,
-            },
-        ))
+        Ok((s, params))
+    }
+
+    /// Arguments are evaluated in the caller's environment, the body in a child
+    /// of the environment the function was defined in.
+    pub(super) fn eval(&self, env: &Env) -> Result<Val, Signal> {
+        self.eval_tail(env)?.finish(env)
     }
 
-    pub(super) fn eval(&self, env: &Env) -> Result<Val, String> {
-        let mut child_env = env.create_child();
+    /// Resolves the callee and evaluates the arguments, but leaves the call
+    /// itself to the caller.
+    pub(super) fn eval_tail(&self, env: &Env) -> Result<Tail, Signal> {
+        let name = match &*self.callee {
+            Expr::BindingUsage(binding_usage) => Some(&binding_usage.name),
+            _ => None,
+        };
+
+        // A named function is looked up rather than evaluated, which would call
+        // it if it has no parameters.
+        let callee = match name {
+            Some(name) => env
+                .get_binding(name)
+                .ok_or_else(|| EvalError::UnboundName {
+                    name: name.clone(),
+                    span: self.span,
+                })?,
+            None => self.callee.eval(env)?,
+        };
 
-        let (param_names, body) = env.get_func(&self.callee)?;
+        let num_expected = match &callee {
+            Val::Func(closure) => closure.params.len(),
+            Val::Native(native) => native.arity,
+            other => {
+                return Err(match name {
+                    Some(name) => EvalError::NotAFunction {
+                        name: name.clone(),
+                        span: self.span,
+                    },
+                    None => EvalError::TypeMismatch {
+                        expected: "function",
+                        found: other.type_name(),
+                        span: self.span,
+                    },
+                }
+                .into())
+            }
+        };
 
-        let (num_expected, num_got) = (param_names.len(), self.params.len());
+        let num_got = self.params.len();
         if num_expected != num_got {
-            return Err(format!(
-                "expected {} parameters, got {}",
-                num_expected, num_got,
-            ));
+            return Err(EvalError::ArityMismatch {
+                name: name.map_or_else(|| "<lambda>".to_string(), Clone::clone),
+                expected: num_expected,
+                got: num_got,
+                span: self.span,
+            }
+            .into());
         }
 
-        for (param_name, param_expr) in param_names.into_iter().zip(&self.params) {
-            let param_val = param_expr.eval(&child_env)?;
-            child_env.store_binding(param_name, param_val);
+        let args: Vec<Val> = self
+            .params
+            .iter()
+            .map(|param| param.eval(env))
+            .collect::<Result<_, _>>()?;
+
+        // Native functions do not grow the eldiro call stack, so they are
+        // called right away.
+        match callee {
+            Val::Native(native) => Ok(Tail::Val(native.call(&args, self.span)?)),
+            Val::Func(closure) => Ok(Tail::Call {
+                closure,
+                args,
+                span: self.span,
+            }),
+            _ => unreachable!("callee was checked to be a function"),
         }
-        body.eval(&mut child_env)
     }
 }
 
@@ -55,19 +112,24 @@ impl FuncCall {
 mod tests {
     use super::super::{BindingUsage, Number};
     use super::*;
+    use crate::eval_in;
     use crate::expr::Op;
     use crate::stmt::Stmt;
 
     #[test]
     fn parse_func_call_with_one_parameter() {
         assert_eq!(
-            FuncCall::new("factorial 10"),
+            Expr::new("factorial 10".into()),
             Ok((
-                "",
-                FuncCall {
-                    callee: "factorial".to_string(),
-                    params: vec![Expr::Number(Number(10))],
-                },
+                "".into(),
+                Expr::FuncCall(FuncCall {
+                    callee: Box::new(Expr::BindingUsage(BindingUsage {
+                        name: "factorial".to_string(),
+                        span: Span::new(0, 9),
+                    })),
+                    params: vec![Expr::Number(Number::from(10))],
+                    span: Span::new(0, 12),
+                }),
             )),
         );
     }
@@ -81,16 +143,21 @@ mod tests {
             vec!["x".to_string()],
             Stmt::Expr(Expr::BindingUsage(BindingUsage {
                 name: "x".to_string(),
+                span: Span::default(),
             })),
         );
 
         assert_eq!(
             FuncCall {
-                callee: "id".to_string(),
-                params: vec![Expr::Number(Number(10))],
+                callee: Box::new(Expr::BindingUsage(BindingUsage {
+                    name: "id".to_string(),
+                    span: Span::default(),
+                })),
+                params: vec![Expr::Number(Number::from(10))],
+                span: Span::default(),
             }
             .eval(&env),
-            Ok(Val::Number(10)),
+            Ok(Val::from(10)),
         );
     }
 
@@ -100,11 +167,42 @@ mod tests {
 
         assert_eq!(
             FuncCall {
-                callee: "i_dont_exist".to_string(),
-                params: vec![Expr::Number(Number(1))],
+                callee: Box::new(Expr::BindingUsage(BindingUsage {
+                    name: "i_dont_exist".to_string(),
+                    span: Span::default(),
+                })),
+                params: vec![Expr::Number(Number::from(1))],
+                span: Span::default(),
+            }
+            .eval(&env),
+            Err(EvalError::UnboundName {
+                name: "i_dont_exist".to_string(),
+                span: Span::default(),
+            }
+            .into()),
+        );
+    }
+
+    #[test]
+    fn eval_func_call_on_binding() {
+        let mut env = Env::default();
+        env.store_binding("x".to_string(), Val::from(1));
+
+        assert_eq!(
+            FuncCall {
+                callee: Box::new(Expr::BindingUsage(BindingUsage {
+                    name: "x".to_string(),
+                    span: Span::default(),
+                })),
+                params: vec![Expr::Number(Number::from(1))],
+                span: Span::new(0, 3),
             }
             .eval(&env),
-            Err("function with name 'i_dont_exist' does not exist".to_string()),
+            Err(EvalError::NotAFunction {
+                name: "x".to_string(),
+                span: Span::new(0, 3),
+            }
+            .into()),
         );
     }
 
@@ -118,21 +216,34 @@ mod tests {
             Stmt::Expr(Expr::Operation {
                 lhs: Box::new(Expr::BindingUsage(BindingUsage {
                     name: "a".to_string(),
+                    span: Span::default(),
                 })),
                 rhs: Box::new(Expr::BindingUsage(BindingUsage {
                     name: "b".to_string(),
+                    span: Span::default(),
                 })),
                 op: Op::Mul,
+                span: Span::default(),
             }),
         );
 
         assert_eq!(
             FuncCall {
-                callee: "mul".to_string(),
-                params: vec![Expr::Number(Number(100))],
+                callee: Box::new(Expr::BindingUsage(BindingUsage {
+                    name: "mul".to_string(),
+                    span: Span::default(),
+                })),
+                params: vec![Expr::Number(Number::from(100))],
+                span: Span::default(),
             }
             .eval(&env),
-            Err("expected 2 parameters, got 1".to_string()),
+            Err(EvalError::ArityMismatch {
+                name: "mul".to_string(),
+                expected: 2,
+                got: 1,
+                span: Span::default(),
+            }
+            .into()),
         );
     }
 
@@ -146,21 +257,235 @@ mod tests {
             Stmt::Expr(Expr::Operation {
                 lhs: Box::new(Expr::BindingUsage(BindingUsage {
                     name: "n".to_string(),
+                    span: Span::default(),
                 })),
                 rhs: Box::new(Expr::BindingUsage(BindingUsage {
                     name: "n".to_string(),
+                    span: Span::default(),
                 })),
                 op: Op::Mul,
+                span: Span::default(),
             }),
         );
 
         assert_eq!(
             FuncCall {
-                callee: "square".to_string(),
-                params: vec![Expr::Number(Number(5)), Expr::Number(Number(42))],
+                callee: Box::new(Expr::BindingUsage(BindingUsage {
+                    name: "square".to_string(),
+                    span: Span::default(),
+                })),
+                params: vec![
+                    Expr::Number(Number::from(5)),
+                    Expr::Number(Number::from(42))
+                ],
+                span: Span::default(),
             }
             .eval(&env),
-            Err("expected 1 parameters, got 2".to_string()),
+            Err(EvalError::ArityMismatch {
+                name: "square".to_string(),
+                expected: 1,
+                got: 2,
+                span: Span::default(),
+            }
+            .into()),
+        );
+    }
+
+    /// Runs `program` on a thread with `stack_size` bytes of stack, giving
+    /// back the displayed value since values cannot cross threads.
+    fn eval_on_thread(
+        stack_size: usize,
+        stack_limit: usize,
+        program: &str,
+    ) -> Result<String, EvalError> {
+        let program = program.to_string();
+        std::thread::Builder::new()
+            .stack_size(stack_size)
+            .spawn(move || {
+                let mut env = Env::default();
+                env.set_stack_limit(stack_limit);
+                eval_in(&mut env, &program).map(|val| val.to_string())
+            })
+            .unwrap()
+            .join()
+            .unwrap()
+    }
+
+    /// The 2 MiB stack Rust gives spawned threads by default.
+    fn eval_on_small_stack(program: &str) -> Result<String, EvalError> {
+        eval_on_thread(2 * 1024 * 1024, Env::DEFAULT_STACK_LIMIT, program)
+    }
+
+    #[test]
+    fn eval_func_call_cannot_see_callers_locals() {
+        let mut env = Env::default();
+
+        assert_eq!(
+            eval_in(&mut env, "fn get => secret\n{ let secret = 1\n get }"),
+            Err(EvalError::UnboundName {
+                name: "secret".to_string(),
+                span: Span::new(10, 16),
+            }),
+        );
+    }
+
+    #[test]
+    fn eval_func_call_uses_defining_scope() {
+        let mut env = Env::default();
+
+        assert_eq!(
+            eval_in(&mut env, "let x = 1\nfn getx => x\n{ let x = 2\n getx }"),
+            Ok(Val::from(1)),
+        );
+    }
+
+    #[test]
+    fn eval_func_call_sees_later_updates_to_defining_scope() {
+        let mut env = Env::default();
+
+        assert_eq!(
+            eval_in(&mut env, "let x = 1\nfn getx => x\nx = 3\ngetx"),
+            Ok(Val::from(3)),
+        );
+    }
+
+    #[test]
+    fn eval_func_defined_in_block_captures_block_locals() {
+        let mut env = Env::default();
+
+        assert_eq!(
+            eval_in(
+                &mut env,
+                "let f = { let n = 10\n fn addn x => x + n\n addn 5 }"
+            ),
+            Ok(Val::Unit),
+        );
+        assert_eq!(env.get_binding("f"), Some(Val::from(15)));
+    }
+
+    #[test]
+    fn eval_func_call_evaluates_arguments_in_callers_scope() {
+        let mut env = Env::default();
+
+        assert_eq!(
+            eval_in(&mut env, "fn id x => x\n{ let y = 4\n id y }"),
+            Ok(Val::from(4)),
+        );
+    }
+
+    #[test]
+    fn eval_nested_calls_up_to_call_depth() {
+        let mut env = Env::default();
+        env.set_max_call_depth(10);
+
+        assert_eq!(
+            eval_in(
+                &mut env,
+                "fn f n => if n == 0 { 0 } else { 1 + f (n - 1) }\nf 9"
+            ),
+            Ok(Val::from(9)),
+        );
+        assert_eq!(
+            eval_in(&mut env, "f 10"),
+            Err(EvalError::StackOverflow {
+                limit: 10,
+                span: Span::new(37, 46),
+            }),
+        );
+    }
+
+    #[test]
+    fn eval_deep_recursion_fails_cleanly() {
+        let mut env = Env::default();
+
+        assert!(matches!(
+            eval_in(
+                &mut env,
+                "fn f n => if n == 0 { 0 } else { 1 + f (n - 1) }\nf 100000"
+            ),
+            Err(EvalError::StackExhausted {
+                limit: Env::DEFAULT_STACK_LIMIT,
+                ..
+            }),
+        ));
+    }
+
+    #[test]
+    fn eval_deep_nesting_within_calls_fits_default_stack() {
+        let result = eval_on_small_stack("fn f n => { if n == 0 { 0 } else { { { [{ { 1 + { if true { f (n - 1) } else { 0 } } } }][0] } } } }\nf 149");
+
+        assert!(
+            matches!(result, Ok(_) | Err(EvalError::StackExhausted { .. })),
+            "{:?}",
+            result,
+        );
+    }
+
+    #[test]
+    fn eval_long_operator_chain_fits_default_stack() {
+        let chain = vec!["1"; 1000].join(" + ");
+        let result = eval_on_small_stack(&chain);
+
+        assert!(
+            matches!(result, Ok(_) | Err(EvalError::StackExhausted { .. })),
+            "{:?}",
+            result,
+        );
+    }
+
+    #[test]
+    fn eval_deep_recursion_fits_default_stack() {
+        let result =
+            eval_on_small_stack("fn f n => if n == 0 { 0 } else { 1 + f (n - 1) }\nf 100000");
+
+        assert!(
+            matches!(result, Err(EvalError::StackExhausted { .. })),
+            "{:?}",
+            result,
+        );
+    }
+
+    #[test]
+    fn eval_deep_recursion_with_raised_stack_limit() {
+        let result = eval_on_thread(
+            64 * 1024 * 1024,
+            48 * 1024 * 1024,
+            "fn sum n => if n == 0 { 0 } else { n + sum (n - 1) }\nsum 1000",
+        );
+
+        assert_eq!(result, Ok("500500".to_string()));
+    }
+
+    #[test]
+    fn eval_tail_recursion_does_not_count_towards_call_depth() {
+        let mut env = Env::default();
+
+        assert_eq!(
+            eval_in(&mut env, "fn count n acc => if n == 0 { acc } else { count (n - 1) (acc + 1) }\ncount 100000 0"),
+            Ok(Val::from(100000)),
+        );
+    }
+
+    #[test]
+    fn eval_mutual_tail_recursion() {
+        let mut env = Env::default();
+
+        assert_eq!(
+            eval_in(&mut env, "fn even n => if n == 0 { true } else { odd (n - 1) }\nfn odd n => if n == 0 { false } else { even (n - 1) }\nodd 10001"),
+            Ok(Val::Bool(true)),
+        );
+    }
+
+    #[test]
+    fn eval_tail_call_after_statements_in_block() {
+        let mut env = Env::default();
+
+        assert_eq!(
+            eval_in(
+                &mut env,
+                "fn down n => { let m = n - 1\n if m == 0 { 0 } else { down m } }\ndown 10000"
+            ),
+            Ok(Val::from(0)),
         );
     }
 }
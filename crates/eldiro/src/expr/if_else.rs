@@ -0,0 +1,279 @@
+use crate::closure::Tail;
+use crate::env::Env;
+use crate::error::{ParseError, Span};
+use crate::signal::Signal;
+use crate::utils::{self, Input, OrParse};
+use crate::val::Val;
+
+use super::{Block, Expr};
+
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) struct IfElse {
+    pub(crate) cond: Box<Expr>,
+    pub(crate) then_branch: Block,
+    pub(crate) else_branch: Option<Box<Expr>>,
+    pub(crate) span: Span,
+}
+
+impl IfElse {
+    pub(super) fn new(s: Input) -> Result<(Input, Self), ParseError> {
+        let start = s;
+
+        let s = utils::keyword("if", s)?;
+        let (s, _) = utils::extract_whitespace(s);
+
+        let (s, cond) = Expr::new_condition(s)?;
+        let span = start.span_to(s);
+
+        let (s, _) = utils::extract_whitespace(s);
+        let (s, then_branch) = Block::new(s)?;
+
+        let (after_ws, _) = utils::extract_whitespace(s);
+        let (s, else_branch) = match utils::keyword("else", after_ws) {
+            Ok(s) => {
+                let (s, _) = utils::extract_whitespace(s);
+                let (s, else_branch) = Self::new(s)
+                    .map(|(s, if_else)| (s, Expr::IfElse(if_else)))
+                    .or_parse(|| Block::new(s).map(|(s, block)| (s, Expr::Block(block))))?;
+
+                (s, Some(Box::new(else_branch)))
+            }
+            Err(_) => (s, None),
+        };
+
+        Ok((
+            s,
+            Self {
+                cond: Box::new(cond),
+                then_branch,
+                else_branch,
+                span,
+            },
+        ))
+    }
+
+    pub(super) fn eval(&self, env: &Env) -> Result<Val, Signal> {
+        self.eval_tail(env)?.finish(env)
+    }
+
+    pub(super) fn eval_tail(&self, env: &Env) -> Result<Tail, Signal> {
+        if self.cond.eval(env)?.expect_bool(self.span)? {
+            self.then_branch.eval_tail(env)
+        } else if let Some(else_branch) = &self.else_branch {
+            else_branch.eval_tail(env)
+        } else {
+            Ok(Tail::Val(Val::Unit))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{BindingUsage, Number, Op};
+    use super::*;
+    use crate::error::EvalError;
+    use crate::stmt::Stmt;
+
+    fn block(n: i32) -> Block {
+        Block {
+            stmts: vec![Stmt::Expr(Expr::Number(Number::from(n)))],
+        }
+    }
+
+    #[test]
+    fn parse_if() {
+        assert_eq!(
+            IfElse::new("if true { 1 }".into()),
+            Ok((
+                "".into(),
+                IfElse {
+                    cond: Box::new(Expr::Bool(true)),
+                    then_branch: block(1),
+                    else_branch: None,
+                    span: Span::new(0, 7),
+                },
+            )),
+        );
+    }
+
+    #[test]
+    fn parse_if_else() {
+        assert_eq!(
+            IfElse::new("if false { 1 } else { 2 }".into()),
+            Ok((
+                "".into(),
+                IfElse {
+                    cond: Box::new(Expr::Bool(false)),
+                    then_branch: block(1),
+                    else_branch: Some(Box::new(Expr::Block(block(2)))),
+                    span: Span::new(0, 8),
+                },
+            )),
+        );
+    }
+
+    #[test]
+    fn parse_if_else_on_new_line() {
+        assert_eq!(
+            IfElse::new("if true { 1 }\nelse { 2 }".into()),
+            Ok((
+                "".into(),
+                IfElse {
+                    cond: Box::new(Expr::Bool(true)),
+                    then_branch: block(1),
+                    else_branch: Some(Box::new(Expr::Block(block(2)))),
+                    span: Span::new(0, 7),
+                },
+            )),
+        );
+    }
+
+    #[test]
+    fn parse_else_if() {
+        assert_eq!(
+            IfElse::new("if a { 1 } else if b { 2 } else { 3 }".into()),
+            Ok((
+                "".into(),
+                IfElse {
+                    cond: Box::new(Expr::BindingUsage(BindingUsage {
+                        name: "a".to_string(),
+                        span: Span::new(3, 4),
+                    })),
+                    then_branch: block(1),
+                    else_branch: Some(Box::new(Expr::IfElse(IfElse {
+                        cond: Box::new(Expr::BindingUsage(BindingUsage {
+                            name: "b".to_string(),
+                            span: Span::new(19, 20),
+                        })),
+                        then_branch: block(2),
+                        else_branch: Some(Box::new(Expr::Block(block(3)))),
+                        span: Span::new(16, 20),
+                    }))),
+                    span: Span::new(0, 4),
+                },
+            )),
+        );
+    }
+
+    #[test]
+    fn parse_if_with_operation_as_condition() {
+        assert_eq!(
+            IfElse::new("if x < 2 { 1 }".into()),
+            Ok((
+                "".into(),
+                IfElse {
+                    cond: Box::new(Expr::Operation {
+                        lhs: Box::new(Expr::BindingUsage(BindingUsage {
+                            name: "x".to_string(),
+                            span: Span::new(3, 4),
+                        })),
+                        rhs: Box::new(Expr::Number(Number::from(2))),
+                        op: Op::Lt,
+                        span: Span::new(3, 8),
+                    }),
+                    then_branch: block(1),
+                    else_branch: None,
+                    span: Span::new(0, 8),
+                },
+            )),
+        );
+    }
+
+    #[test]
+    fn parse_if_without_block() {
+        assert_eq!(
+            IfElse::new("if true 1".into()),
+            Err(ParseError {
+                span: Span::new(8, 9),
+                line: 1,
+                column: 9,
+                expected: vec!["`{`".to_string()],
+            }),
+        );
+    }
+
+    #[test]
+    fn parse_else_without_block() {
+        assert_eq!(
+            IfElse::new("if true { 1 } else 2".into()),
+            Err(ParseError {
+                span: Span::new(19, 20),
+                line: 1,
+                column: 20,
+                expected: vec!["`if`".to_string(), "`{`".to_string()],
+            }),
+        );
+    }
+
+    #[test]
+    fn eval_if_with_true_condition() {
+        assert_eq!(
+            IfElse::new("if true { 1 } else { 2 }".into())
+                .unwrap()
+                .1
+                .eval(&Env::default()),
+            Ok(Val::from(1)),
+        );
+    }
+
+    #[test]
+    fn eval_if_with_false_condition() {
+        assert_eq!(
+            IfElse::new("if 1 > 2 { 1 } else { 2 }".into())
+                .unwrap()
+                .1
+                .eval(&Env::default()),
+            Ok(Val::from(2)),
+        );
+    }
+
+    #[test]
+    fn eval_else_if() {
+        let mut env = Env::default();
+        env.store_binding("x".to_string(), Val::from(5));
+
+        assert_eq!(
+            IfElse::new("if x < 0 { 1 } else if x < 10 { 2 } else { 3 }".into())
+                .unwrap()
+                .1
+                .eval(&env),
+            Ok(Val::from(2)),
+        );
+    }
+
+    #[test]
+    fn eval_if_without_else_is_unit() {
+        assert_eq!(
+            IfElse::new("if false { 1 }".into())
+                .unwrap()
+                .1
+                .eval(&Env::default()),
+            Ok(Val::Unit),
+        );
+    }
+
+    #[test]
+    fn eval_if_with_non_bool_condition() {
+        assert_eq!(
+            IfElse::new("if 1 { 1 }".into())
+                .unwrap()
+                .1
+                .eval(&Env::default()),
+            Err(EvalError::TypeMismatch {
+                expected: "bool",
+                found: "number",
+                span: Span::new(0, 4),
+            }
+            .into()),
+        );
+    }
+
+    #[test]
+    fn eval_branch_bindings_do_not_leak() {
+        let mut env = Env::default();
+        let (_, stmt) = Stmt::new("if true { let y = 1 }".into()).unwrap();
+        stmt.eval(&mut env).unwrap();
+
+        assert_eq!(env.get_binding("y"), None);
+    }
+}
@@ -0,0 +1,233 @@
+use std::rc::Rc;
+
+use crate::closure::Closure;
+use crate::env::Env;
+use crate::error::ParseError;
+use crate::stmt::Stmt;
+use crate::utils::{self, Input};
+use crate::val::Val;
+
+use super::Expr;
+
+/// An anonymous function such as `|x y| x + y`. The body extends as far to the
+/// right as possible.
+///
+/// It needs at least one parameter: a function without any is called wherever
+/// it is named, so it could not be passed around as a value.
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) struct Lambda {
+    pub(crate) params: Vec<String>,
+    pub(crate) body: Box<Expr>,
+}
+
+impl Lambda {
+    pub(super) fn new(s: Input) -> Result<(Input, Self), ParseError> {
+        // `||` is lexed as a single token, so it needs handling on its own.
+        if utils::tag("||", s).is_ok() {
+            return Err(ParseError::new(s.split_at(1).0, "a parameter"));
+        }
+
+        let s = utils::tag("|", s)?;
+        let (s, _) = utils::extract_whitespace(s);
+
+        let (after_params, params) = utils::sequence_until(
+            |s| utils::extract_ident(s).map(|(s, ident)| (s, ident.to_string())),
+            "|",
+            s,
+        )?;
+        if params.is_empty() {
+            return Err(ParseError::new(s, "a parameter"));
+        }
+        let s = after_params;
+        let (s, _) = utils::extract_whitespace(s);
+
+        let (s, body) = Expr::new(s)?;
+
+        Ok((
+            s,
+            Self {
+                params,
+                body: Box::new(body),
+            },
+        ))
+    }
+
+    pub(super) fn eval(&self, env: &Env) -> Val {
+        Val::Func(Rc::new(Closure {
+            params: self.params.clone(),
+            body: Rc::new(Stmt::Expr((*self.body).clone())),
+            scope: env.capture(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{BindingUsage, Op};
+    use super::*;
+    use crate::error::{EvalError, Span};
+    use crate::eval_in;
+
+    #[test]
+    fn parse_lambda() {
+        assert_eq!(
+            Lambda::new("|x y| x + y".into()),
+            Ok((
+                "".into(),
+                Lambda {
+                    params: vec!["x".to_string(), "y".to_string()],
+                    body: Box::new(Expr::Operation {
+                        lhs: Box::new(Expr::BindingUsage(BindingUsage {
+                            name: "x".to_string(),
+                            span: Span::new(6, 7),
+                        })),
+                        rhs: Box::new(Expr::BindingUsage(BindingUsage {
+                            name: "y".to_string(),
+                            span: Span::new(10, 11),
+                        })),
+                        op: Op::Add,
+                        span: Span::new(6, 11),
+                    }),
+                },
+            )),
+        );
+    }
+
+    #[test]
+    fn parse_lambda_without_params() {
+        assert_eq!(
+            Lambda::new("|| 1".into()),
+            Err(ParseError {
+                span: Span::new(1, 2),
+                line: 1,
+                column: 2,
+                expected: vec!["a parameter".to_string()],
+            }),
+        );
+    }
+
+    #[test]
+    fn parse_spaced_lambda_without_params() {
+        assert_eq!(
+            Lambda::new("| | 1".into()).map_err(|error| error.span),
+            Err(Span::new(2, 3)),
+        );
+    }
+
+    #[test]
+    fn parse_binding_of_lambda_without_params() {
+        assert_eq!(
+            crate::parse("let f = || 42\n[f]").map_err(|error| error.to_string()),
+            Err("1:10: expected a parameter".to_string()),
+        );
+    }
+
+    #[test]
+    fn eval_lambda_called_through_binding() {
+        let mut env = Env::default();
+
+        assert_eq!(
+            eval_in(&mut env, "let add = |x y| x + y\nadd 2 3"),
+            Ok(Val::from(5)),
+        );
+    }
+
+    #[test]
+    fn eval_lambda_called_directly() {
+        assert_eq!(
+            eval_in(&mut Env::default(), "(|x| x * 2) 21"),
+            Ok(Val::from(42)),
+        );
+    }
+
+    #[test]
+    fn eval_func_passed_as_argument() {
+        let mut env = Env::default();
+
+        assert_eq!(
+            eval_in(
+                &mut env,
+                "fn twice f x => f (f x)\nfn inc n => n + 1\ntwice inc 5"
+            ),
+            Ok(Val::from(7)),
+        );
+    }
+
+    #[test]
+    fn eval_lambda_passed_as_argument() {
+        let mut env = Env::default();
+
+        assert_eq!(
+            eval_in(&mut env, "fn twice f x => f (f x)\ntwice (|n| n * 3) 2"),
+            Ok(Val::from(18)),
+        );
+    }
+
+    #[test]
+    fn eval_returned_closure_keeps_captured_bindings() {
+        let mut env = Env::default();
+
+        assert_eq!(
+            eval_in(
+                &mut env,
+                "fn adder n => |x| x + n\nlet add10 = adder 10\nadd10 5"
+            ),
+            Ok(Val::from(15)),
+        );
+        assert_eq!(eval_in(&mut env, "(adder 1) 1"), Ok(Val::from(2)));
+    }
+
+    #[test]
+    fn eval_closures_share_captured_state() {
+        let mut env = Env::default();
+
+        assert_eq!(
+            eval_in(&mut env, "fn counter => { let n = 0\n |step| { n += step\n n } }\nlet count = counter\ncount 2\ncount 3"),
+            Ok(Val::from(5)),
+        );
+    }
+
+    #[test]
+    fn returned_lambdas_are_freed_with_env() {
+        let mut env = Env::default();
+        let host = Rc::new(());
+        let captured = Rc::clone(&host);
+        env.register_fn("probe", 0, move |_| {
+            let _ = &captured;
+            Ok::<_, String>(Val::Unit)
+        });
+        eval_in(
+            &mut env,
+            "let inc = {\n  let n = 1\n  |x| x + n\n}\nfn adder n => |x| x + n\nlet add10 = adder 10",
+        )
+        .unwrap();
+
+        drop(env);
+        assert_eq!(Rc::strong_count(&host), 1);
+    }
+
+    #[test]
+    fn eval_call_of_non_function_expression() {
+        assert_eq!(
+            eval_in(&mut Env::default(), "(1 + 1) 2"),
+            Err(EvalError::TypeMismatch {
+                expected: "function",
+                found: "number",
+                span: Span::new(0, 9),
+            }),
+        );
+    }
+
+    #[test]
+    fn eval_lambda_with_wrong_number_of_arguments() {
+        assert_eq!(
+            eval_in(&mut Env::default(), "(|x y| x) 1"),
+            Err(EvalError::ArityMismatch {
+                name: "<lambda>".to_string(),
+                expected: 2,
+                got: 1,
+                span: Span::new(0, 11),
+            }),
+        );
+    }
+}
@@ -0,0 +1,261 @@
+use crate::env::Env;
+use crate::error::{EvalError, ParseError, Span};
+use crate::int;
+use crate::signal::Signal;
+use crate::utils::{self, Input};
+use crate::val::Val;
+
+use super::Expr;
+
+/// A list literal such as `[1, 2, 3]`. A trailing comma is allowed.
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) struct List {
+    pub(crate) items: Vec<Expr>,
+}
+
+impl List {
+    pub(super) fn new(s: Input) -> Result<(Input, Self), ParseError> {
+        let s = utils::tag("[", s)?;
+        let (mut s, _) = utils::extract_whitespace(s);
+        let mut items = Vec::new();
+
+        loop {
+            if let Ok(rest) = utils::tag("]", s) {
+                return Ok((rest, Self { items }));
+            }
+
+            let (rest, item) =
+                Expr::new(s).map_err(|error| error.merge(ParseError::new(s, "`]`")))?;
+            items.push(item);
+            let (rest, _) = utils::extract_whitespace(rest);
+
+            s = match utils::tag(",", rest) {
+                Ok(rest) => utils::extract_whitespace(rest).0,
+                Err(error) => {
+                    let rest = utils::tag("]", rest).map_err(|close| error.merge(close))?;
+                    return Ok((rest, Self { items }));
+                }
+            };
+        }
+    }
+
+    pub(super) fn eval(&self, env: &Env) -> Result<Val, Signal> {
+        let items = self
+            .items
+            .iter()
+            .map(|item| item.eval(env))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Val::List(items))
+    }
+}
+
+/// Looks up an element of a list, as in `xs[0]`, or the value of a key in a
+/// map, as in `m["key"]` or its shorthand `m.key`. The `[` or `.` has to
+/// follow right after the indexed expression, since `f [0]` passes a list to
+/// `f`.
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) struct Index {
+    pub(crate) target: Box<Expr>,
+    pub(crate) index: Box<Expr>,
+    pub(crate) span: Span,
+}
+
+impl Index {
+    /// Parses any number of indexes and field accesses following `target`,
+    /// which starts at `start`.
+    pub(super) fn new_chain<'a>(
+        start: Input<'a>,
+        mut s: Input<'a>,
+        mut target: Expr,
+    ) -> Result<(Input<'a>, Expr), ParseError> {
+        loop {
+            let index = if let Ok(rest) = utils::tag("[", s) {
+                let (rest, _) = utils::extract_whitespace(rest);
+                let (rest, index) = Expr::new(rest)?;
+                let (rest, _) = utils::extract_whitespace(rest);
+                s = utils::tag("]", rest)?;
+                index
+            } else if let Some((rest, key)) = utils::tag(".", s)
+                .ok()
+                .and_then(|rest| utils::extract_ident(rest).ok())
+            {
+                s = rest;
+                Expr::Str(key.to_string())
+            } else {
+                break;
+            };
+
+            target = Expr::Index(Self {
+                target: Box::new(target),
+                index: Box::new(index),
+                span: start.span_to(s),
+            });
+        }
+
+        Ok((s, target))
+    }
+
+    pub(super) fn eval(&self, env: &Env) -> Result<Val, Signal> {
+        env.check_stack(self.span)?;
+        let items = match self.target.eval(env)? {
+            Val::Map(entries) => {
+                let key = self.index.eval(env)?.expect_str(self.span)?;
+                return entries.get(&key).cloned().ok_or_else(|| {
+                    EvalError::MissingKey {
+                        key,
+                        span: self.span,
+                    }
+                    .into()
+                });
+            }
+            target => target.expect_list(self.span)?,
+        };
+        let index = self.index.eval(env)?.expect_int(self.span)?;
+
+        int::to_usize(&index)
+            .and_then(|idx| items.get(idx).cloned())
+            .ok_or_else(|| {
+                EvalError::IndexOutOfBounds {
+                    index,
+                    len: items.len(),
+                    span: self.span,
+                }
+                .into()
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{BindingUsage, Number, Op};
+    use super::*;
+    use crate::eval;
+    use crate::int::Int;
+
+    #[test]
+    fn parse_list() {
+        assert_eq!(
+            List::new("[1, 2 + 3,\n 4,]".into()),
+            Ok((
+                "".into(),
+                List {
+                    items: vec![
+                        Expr::Number(Number::from(1)),
+                        Expr::Operation {
+                            lhs: Box::new(Expr::Number(Number::from(2))),
+                            rhs: Box::new(Expr::Number(Number::from(3))),
+                            op: Op::Add,
+                            span: Span::new(4, 9),
+                        },
+                        Expr::Number(Number::from(4)),
+                    ],
+                },
+            )),
+        );
+    }
+
+    #[test]
+    fn parse_empty_list() {
+        assert_eq!(
+            List::new("[ ]".into()),
+            Ok(("".into(), List { items: Vec::new() })),
+        );
+    }
+
+    #[test]
+    fn parse_list_without_comma() {
+        assert_eq!(
+            List::new("[1 2]".into()),
+            Err(ParseError {
+                span: Span::new(3, 4),
+                line: 1,
+                column: 4,
+                expected: vec!["`,`".to_string(), "`]`".to_string()],
+            }),
+        );
+    }
+
+    #[test]
+    fn parse_index() {
+        assert_eq!(
+            Expr::new("xs[i]".into()),
+            Ok((
+                "".into(),
+                Expr::Index(Index {
+                    target: Box::new(Expr::BindingUsage(BindingUsage {
+                        name: "xs".to_string(),
+                        span: Span::new(0, 2),
+                    })),
+                    index: Box::new(Expr::BindingUsage(BindingUsage {
+                        name: "i".to_string(),
+                        span: Span::new(3, 4),
+                    })),
+                    span: Span::new(0, 5),
+                }),
+            )),
+        );
+    }
+
+    #[test]
+    fn parse_list_passed_to_func_call() {
+        assert!(matches!(
+            Expr::new("f [0]".into()),
+            Ok((_, Expr::FuncCall(_))),
+        ));
+    }
+
+    #[test]
+    fn eval_list() {
+        assert_eq!(
+            eval("[1, 1 + 1, \"three\"]"),
+            Ok(Val::List(vec![
+                Val::from(1),
+                Val::from(2),
+                Val::Str("three".to_string()),
+            ])),
+        );
+    }
+
+    #[test]
+    fn eval_index() {
+        assert_eq!(eval("[10, 20, 30][1]"), Ok(Val::from(20)));
+        assert_eq!(eval("[[1, 2], [3, 4]][1][0]"), Ok(Val::from(3)));
+    }
+
+    #[test]
+    fn eval_index_out_of_bounds() {
+        assert_eq!(
+            eval("[1, 2][2]"),
+            Err(EvalError::IndexOutOfBounds {
+                index: Int::from(2),
+                len: 2,
+                span: Span::new(0, 9),
+            }),
+        );
+    }
+
+    #[test]
+    fn eval_negative_index() {
+        assert_eq!(
+            eval("[1][-1]"),
+            Err(EvalError::IndexOutOfBounds {
+                index: Int::from(-1),
+                len: 1,
+                span: Span::new(0, 7),
+            }),
+        );
+    }
+
+    #[test]
+    fn eval_index_of_non_list() {
+        assert_eq!(
+            eval("1[0]"),
+            Err(EvalError::TypeMismatch {
+                expected: "list",
+                found: "number",
+                span: Span::new(0, 4),
+            }),
+        );
+    }
+}
@@ -0,0 +1,363 @@
+use crate::env::Env;
+use crate::error::{ParseError, Span};
+use crate::signal::Signal;
+use crate::utils::{self, Input};
+use crate::val::Val;
+
+use super::{Block, Expr};
+
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) struct While {
+    pub(crate) cond: Box<Expr>,
+    pub(crate) body: Block,
+    pub(crate) span: Span,
+}
+
+impl While {
+    pub(super) fn new(s: Input) -> Result<(Input, Self), ParseError> {
+        let start = s;
+
+        let s = utils::keyword("while", s)?;
+        let (s, _) = utils::extract_whitespace(s);
+
+        let (s, cond) = Expr::new_condition(s)?;
+        let span = start.span_to(s);
+
+        let (s, _) = utils::extract_whitespace(s);
+        let (s, body) = Block::new(s)?;
+
+        Ok((
+            s,
+            Self {
+                cond: Box::new(cond),
+                body,
+                span,
+            },
+        ))
+    }
+
+    /// A `while` loop always evaluates to unit, even when left with a `break`
+    /// that carries a value.
+    pub(super) fn eval(&self, env: &Env) -> Result<Val, Signal> {
+        while self.cond.eval(env)?.expect_bool(self.span)? {
+            if run_body(&self.body, env)?.is_some() {
+                break;
+            }
+        }
+
+        Ok(Val::Unit)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) struct Loop {
+    pub(crate) body: Block,
+}
+
+impl Loop {
+    pub(super) fn new(s: Input) -> Result<(Input, Self), ParseError> {
+        let s = utils::keyword("loop", s)?;
+        let (s, _) = utils::extract_whitespace(s);
+
+        let (s, body) = Block::new(s)?;
+
+        Ok((s, Self { body }))
+    }
+
+    pub(super) fn eval(&self, env: &Env) -> Result<Val, Signal> {
+        loop {
+            if let Some(val) = run_body(&self.body, env)? {
+                return Ok(val);
+            }
+        }
+    }
+}
+
+/// Runs the body once for every element of a list or every key of a map, with
+/// the element bound to `name`. Like `while`, it evaluates to unit.
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) struct For {
+    pub(crate) name: String,
+    pub(crate) iterable: Box<Expr>,
+    pub(crate) body: Block,
+    pub(crate) span: Span,
+}
+
+impl For {
+    pub(super) fn new(s: Input) -> Result<(Input, Self), ParseError> {
+        let start = s;
+
+        let s = utils::keyword("for", s)?;
+        let (s, _) = utils::extract_whitespace1(s)?;
+        let (s, name) = utils::extract_ident(s)?;
+        let (s, _) = utils::extract_whitespace1(s)?;
+        let s = utils::keyword("in", s)?;
+        let (s, _) = utils::extract_whitespace(s);
+
+        let (s, iterable) = Expr::new_condition(s)?;
+        let span = start.span_to(s);
+
+        let (s, _) = utils::extract_whitespace(s);
+        let (s, body) = Block::new(s)?;
+
+        Ok((
+            s,
+            Self {
+                name: name.to_string(),
+                iterable: Box::new(iterable),
+                body,
+                span,
+            },
+        ))
+    }
+
+    pub(super) fn eval(&self, env: &Env) -> Result<Val, Signal> {
+        let items = match self.iterable.eval(env)? {
+            Val::Map(entries) => entries.into_keys().map(Val::Str).collect(),
+            iterable => iterable.expect_list(self.span)?,
+        };
+
+        for item in items {
+            let mut iteration_env = env.create_child();
+            iteration_env.store_binding(self.name.clone(), item);
+
+            if run_body(&self.body, &iteration_env)?.is_some() {
+                break;
+            }
+        }
+
+        Ok(Val::Unit)
+    }
+}
+
+/// Runs one iteration, returning the value of a `break` if the loop should
+/// stop.
+fn run_body(body: &Block, env: &Env) -> Result<Option<Val>, Signal> {
+    match body.eval(env) {
+        Ok(_) | Err(Signal::Continue { .. }) => Ok(None),
+        Err(Signal::Break { val, .. }) => Ok(Some(val)),
+        Err(signal) => Err(signal),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{BindingUsage, Number};
+    use super::*;
+    use crate::error::EvalError;
+    use crate::stmt::Stmt;
+
+    #[test]
+    fn parse_while() {
+        assert_eq!(
+            While::new("while running { 1 }".into()),
+            Ok((
+                "".into(),
+                While {
+                    cond: Box::new(Expr::BindingUsage(BindingUsage {
+                        name: "running".to_string(),
+                        span: Span::new(6, 13),
+                    })),
+                    body: Block {
+                        stmts: vec![Stmt::Expr(Expr::Number(Number::from(1)))],
+                    },
+                    span: Span::new(0, 13),
+                },
+            )),
+        );
+    }
+
+    #[test]
+    fn parse_loop() {
+        assert_eq!(
+            Loop::new("loop { break }".into()),
+            Ok((
+                "".into(),
+                Loop {
+                    body: Block {
+                        stmts: vec![Stmt::Break {
+                            val: None,
+                            span: Span::new(7, 12),
+                        }],
+                    },
+                },
+            )),
+        );
+    }
+
+    #[test]
+    fn eval_while_with_false_condition() {
+        assert_eq!(
+            While::new("while false { 1 / 0 }".into())
+                .unwrap()
+                .1
+                .eval(&Env::default()),
+            Ok(Val::Unit),
+        );
+    }
+
+    #[test]
+    fn eval_while_with_break() {
+        assert_eq!(
+            While::new("while true { break 5 }".into())
+                .unwrap()
+                .1
+                .eval(&Env::default()),
+            Ok(Val::Unit),
+        );
+    }
+
+    #[test]
+    fn eval_while_with_non_bool_condition() {
+        assert_eq!(
+            While::new("while 1 { }".into())
+                .unwrap()
+                .1
+                .eval(&Env::default()),
+            Err(EvalError::TypeMismatch {
+                expected: "bool",
+                found: "number",
+                span: Span::new(0, 7),
+            }
+            .into()),
+        );
+    }
+
+    #[test]
+    fn eval_loop_evaluates_to_break_value() {
+        assert_eq!(
+            Loop::new("loop { let x = 2\n break x * 3 }".into())
+                .unwrap()
+                .1
+                .eval(&Env::default()),
+            Ok(Val::from(6)),
+        );
+    }
+
+    #[test]
+    fn eval_loop_with_break_in_nested_block() {
+        assert_eq!(
+            Loop::new("loop { if true { break } }".into())
+                .unwrap()
+                .1
+                .eval(&Env::default()),
+            Ok(Val::Unit),
+        );
+    }
+
+    #[test]
+    fn eval_loop_propagates_errors() {
+        assert_eq!(
+            Loop::new("loop { 1 / 0 }".into())
+                .unwrap()
+                .1
+                .eval(&Env::default()),
+            Err(EvalError::DivisionByZero {
+                span: Span::new(7, 12),
+            }
+            .into()),
+        );
+    }
+
+    #[test]
+    fn parse_for() {
+        assert_eq!(
+            For::new("for x in xs { x }".into()),
+            Ok((
+                "".into(),
+                For {
+                    name: "x".to_string(),
+                    iterable: Box::new(Expr::BindingUsage(BindingUsage {
+                        name: "xs".to_string(),
+                        span: Span::new(9, 11),
+                    })),
+                    body: Block {
+                        stmts: vec![Stmt::Expr(Expr::BindingUsage(BindingUsage {
+                            name: "x".to_string(),
+                            span: Span::new(14, 15),
+                        }))],
+                    },
+                    span: Span::new(0, 11),
+                },
+            )),
+        );
+    }
+
+    #[test]
+    fn eval_for_over_list() {
+        let mut env = Env::default();
+        let (_, def) = Stmt::new("let total = 0".into()).unwrap();
+        def.eval(&mut env).unwrap();
+
+        let (_, stmt) =
+            Stmt::new("for x in [1, 2, 3, 4] { if x == 3 { continue }\n total += x }".into())
+                .unwrap();
+        assert_eq!(stmt.eval(&mut env), Ok(Val::Unit));
+        assert_eq!(env.get_binding("total"), Some(Val::from(7)));
+    }
+
+    #[test]
+    fn eval_for_with_semicolon_separated_body() {
+        let mut env = Env::default();
+        let (_, def) = Stmt::new("let s = 0".into()).unwrap();
+        def.eval(&mut env).unwrap();
+
+        let (_, stmt) =
+            Stmt::new("for x in [1, 2, 3] { if x == 2 { continue }; s += x }".into()).unwrap();
+        stmt.eval(&mut env).unwrap();
+        assert_eq!(env.get_binding("s"), Some(Val::from(4)));
+    }
+
+    #[test]
+    fn eval_for_with_break() {
+        let mut env = Env::default();
+        let (_, def) = Stmt::new("let last = 0".into()).unwrap();
+        def.eval(&mut env).unwrap();
+
+        let (_, stmt) =
+            Stmt::new("for x in [1, 2, 3] { if x > 1 { break }\n last = x }".into()).unwrap();
+        stmt.eval(&mut env).unwrap();
+        assert_eq!(env.get_binding("last"), Some(Val::from(1)));
+    }
+
+    #[test]
+    fn eval_for_over_map_keys_in_order() {
+        let mut env = Env::default();
+        let (_, def) = Stmt::new("let keys = \"\"".into()).unwrap();
+        def.eval(&mut env).unwrap();
+
+        let (_, stmt) =
+            Stmt::new("for k in { \"b\": 1, \"c\": 2, \"a\": 3 } { keys += k }".into()).unwrap();
+        stmt.eval(&mut env).unwrap();
+        assert_eq!(env.get_binding("keys"), Some(Val::Str("abc".to_string())));
+    }
+
+    #[test]
+    fn eval_for_over_non_list() {
+        assert_eq!(
+            For::new("for x in 5 { }".into())
+                .unwrap()
+                .1
+                .eval(&Env::default()),
+            Err(EvalError::TypeMismatch {
+                expected: "list",
+                found: "number",
+                span: Span::new(0, 10),
+            }
+            .into()),
+        );
+    }
+
+    #[test]
+    fn eval_continue_skips_rest_of_body() {
+        assert_eq!(
+            Block::new("{ continue\n 1 / 0 }".into())
+                .unwrap()
+                .1
+                .eval(&Env::default()),
+            Err(Signal::Continue {
+                span: Span::new(2, 10),
+            }),
+        );
+    }
+}
@@ -0,0 +1,228 @@
+use std::collections::BTreeMap;
+
+use crate::env::Env;
+use crate::error::ParseError;
+use crate::signal::Signal;
+use crate::utils::{self, Input};
+use crate::val::Val;
+
+use super::Expr;
+
+/// A map literal such as `{ "a": 1, "b": 2 }`, or `{:}` for an empty map.
+/// Keys are string literals; a trailing comma is allowed.
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) struct Map {
+    pub(crate) entries: Vec<(String, Expr)>,
+}
+
+impl Map {
+    /// Whether the `{` at the start of `s` opens a map rather than a block,
+    /// which is the case if a key and its `:` follow.
+    pub(super) fn starts_at(s: Input) -> bool {
+        let Ok(s) = utils::tag("{", s) else {
+            return false;
+        };
+        let (s, _) = utils::extract_whitespace(s);
+
+        let s = match utils::extract_string(s) {
+            Ok((s, _)) => utils::extract_whitespace(s).0,
+            Err(_) => s,
+        };
+        utils::tag(":", s).is_ok()
+    }
+
+    pub(super) fn new(s: Input) -> Result<(Input, Self), ParseError> {
+        let s = utils::tag("{", s)?;
+        let (s, _) = utils::extract_whitespace(s);
+
+        if let Ok(s) = utils::tag(":", s) {
+            let (s, _) = utils::extract_whitespace(s);
+            let s = utils::tag("}", s)?;
+            return Ok((
+                s,
+                Self {
+                    entries: Vec::new(),
+                },
+            ));
+        }
+
+        let mut s = s;
+        let mut entries = Vec::new();
+        loop {
+            if let Ok(rest) = utils::tag("}", s) {
+                return Ok((rest, Self { entries }));
+            }
+
+            let (rest, key) =
+                utils::extract_string(s).map_err(|error| error.merge(ParseError::new(s, "`}`")))?;
+            let (rest, _) = utils::extract_whitespace(rest);
+            let rest = utils::tag(":", rest)?;
+            let (rest, _) = utils::extract_whitespace(rest);
+
+            let (rest, val) = Expr::new(rest)?;
+            entries.push((key, val));
+            let (rest, _) = utils::extract_whitespace(rest);
+
+            s = match utils::tag(",", rest) {
+                Ok(rest) => utils::extract_whitespace(rest).0,
+                Err(error) => {
+                    let rest = utils::tag("}", rest).map_err(|close| error.merge(close))?;
+                    return Ok((rest, Self { entries }));
+                }
+            };
+        }
+    }
+
+    /// A key that appears more than once keeps its last value.
+    pub(super) fn eval(&self, env: &Env) -> Result<Val, Signal> {
+        let mut entries = BTreeMap::new();
+        for (key, val) in &self.entries {
+            entries.insert(key.clone(), val.eval(env)?);
+        }
+
+        Ok(Val::Map(entries))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{Block, Index, Number};
+    use super::*;
+    use crate::error::{EvalError, Span};
+    use crate::eval;
+    use crate::stmt::Stmt;
+
+    fn map(entries: &[(&str, Val)]) -> Val {
+        Val::Map(
+            entries
+                .iter()
+                .map(|(key, val)| (key.to_string(), val.clone()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn parse_map() {
+        assert_eq!(
+            Expr::new("{ \"a\": 1,\n \"b\" : 2, }".into()),
+            Ok((
+                "".into(),
+                Expr::Map(Map {
+                    entries: vec![
+                        ("a".to_string(), Expr::Number(Number::from(1))),
+                        ("b".to_string(), Expr::Number(Number::from(2))),
+                    ],
+                }),
+            )),
+        );
+    }
+
+    #[test]
+    fn parse_empty_map() {
+        assert_eq!(
+            Expr::new("{ : }".into()),
+            Ok((
+                "".into(),
+                Expr::Map(Map {
+                    entries: Vec::new()
+                })
+            )),
+        );
+    }
+
+    #[test]
+    fn parse_block_starting_with_string() {
+        assert_eq!(
+            Expr::new("{ \"a\" }".into()),
+            Ok((
+                "".into(),
+                Expr::Block(Block {
+                    stmts: vec![Stmt::Expr(Expr::Str("a".to_string()))],
+                }),
+            )),
+        );
+    }
+
+    #[test]
+    fn parse_map_with_missing_colon() {
+        assert_eq!(
+            Map::new("{ \"a\": 1, \"b\" 2 }".into()),
+            Err(ParseError {
+                span: Span::new(14, 15),
+                line: 1,
+                column: 15,
+                expected: vec!["`:`".to_string()],
+            }),
+        );
+    }
+
+    #[test]
+    fn parse_field_access() {
+        assert_eq!(
+            Expr::new("m.key".into()),
+            Ok((
+                "".into(),
+                Expr::Index(Index {
+                    target: Box::new(Expr::BindingUsage(super::super::BindingUsage {
+                        name: "m".to_string(),
+                        span: Span::new(0, 1),
+                    })),
+                    index: Box::new(Expr::Str("key".to_string())),
+                    span: Span::new(0, 5),
+                }),
+            )),
+        );
+    }
+
+    #[test]
+    fn eval_map_sorted_by_key() {
+        let val = eval("{ \"b\": 1 + 1, \"a\": true, \"b\": 3 }").unwrap();
+
+        assert_eq!(val, map(&[("a", Val::Bool(true)), ("b", Val::from(3))]));
+        assert_eq!(val.to_string(), r#"{"a": true, "b": 3}"#);
+    }
+
+    #[test]
+    fn eval_field_access() {
+        let mut env = Env::default();
+        let (_, def) = Stmt::new("let m = { \"x\": { \"y\": [1, 2] } }".into()).unwrap();
+        def.eval(&mut env).unwrap();
+
+        for access in ["m.x.y[1]", "m[\"x\"][\"y\"][1]"] {
+            let (_, expr) = Expr::new(access.into()).unwrap();
+            assert_eq!(expr.eval(&env), Ok(Val::from(2)));
+        }
+    }
+
+    #[test]
+    fn eval_missing_key() {
+        assert_eq!(
+            eval("{ \"a\": 1 }.b"),
+            Err(EvalError::MissingKey {
+                key: "b".to_string(),
+                span: Span::new(0, 12),
+            }),
+        );
+    }
+
+    #[test]
+    fn eval_map_with_non_string_key() {
+        assert_eq!(
+            eval("{ \"a\": 1 }[0]"),
+            Err(EvalError::TypeMismatch {
+                expected: "string",
+                found: "number",
+                span: Span::new(0, 13),
+            }),
+        );
+    }
+
+    #[test]
+    fn eval_map_equality() {
+        assert_eq!(
+            eval("{ \"a\": 1, \"b\": 2 } == { \"b\": 2, \"a\": 1 }"),
+            Ok(Val::Bool(true)),
+        );
+        assert_eq!(eval("{ \"a\": 1 } != { \"a\": 2 }"), Ok(Val::Bool(true)));
+    }
+}
@@ -1,5 +1,8 @@
+use crate::error::ParseError;
+use crate::signal::Signal;
 use crate::stmt::Stmt;
-use crate::{utils, Env, Val};
+use crate::utils::{self, Input};
+use crate::{Env, Val};
 
 #[derive(Debug, PartialEq, Clone)]
 pub(crate) struct FuncDef {
@@ -9,20 +12,18 @@ pub(crate) struct FuncDef {
 }
 
 impl FuncDef {
-    pub(crate) fn new(s: &str) -> Result<(&str, Self), String> {
-        let s = utils::tag("fn", s)?;
+    pub(crate) fn new(s: Input) -> Result<(Input, Self), ParseError> {
+        let s = utils::keyword("fn", s)?;
         let (s, _) = utils::extract_whitespace1(s)?;
 
         let (s, name) = utils::extract_ident(s)?;
         let (s, _) = utils::extract_whitespace(s);
 
-        let (s, params) = utils::sequence(
+        let (s, params) = utils::sequence_until(
             |s| utils::extract_ident(s).map(|(s, ident)| (s, ident.to_string())),
+            "=>",
             s,
-            None,
         )?;
-
-        let s = utils::tag("=>", s)?;
         let (s, _) = utils::extract_whitespace(s);
 
         let (s, body) = Stmt::new(s)?;
@@ -37,7 +38,7 @@ impl FuncDef {
         ))
     }
 
-    pub(crate) fn eval(&self, env: &mut Env) -> Result<Val, String> {
+    pub(crate) fn eval(&self, env: &mut Env) -> Result<Val, Signal> {
         env.store_func(self.name.clone(), self.params.clone(), *self.body.clone());
         Ok(Val::Unit)
     }
@@ -0,0 +1,201 @@
+//! The integers behind [`Val::Number`](crate::Val::Number): 64 bits wide by
+//! default, or of arbitrary size with the `bigint` feature, in which case
+//! arithmetic never overflows.
+
+use crate::env::Arithmetic;
+use crate::expr::Op;
+
+#[cfg(not(feature = "bigint"))]
+pub type Int = i64;
+
+#[cfg(feature = "bigint")]
+pub type Int = num_bigint::BigInt;
+
+/// Returns `None` if the result does not fit and `arithmetic` says that is an
+/// error, when dividing by zero, or for non-arithmetic operators.
+#[cfg(not(feature = "bigint"))]
+pub(crate) fn apply(op: &Op, lhs: Int, rhs: Int, arithmetic: Arithmetic) -> Option<Int> {
+    if *op == Op::Div && rhs == 0 {
+        return None;
+    }
+
+    match (arithmetic, op) {
+        (Arithmetic::Checked, Op::Add) => lhs.checked_add(rhs),
+        (Arithmetic::Checked, Op::Sub) => lhs.checked_sub(rhs),
+        (Arithmetic::Checked, Op::Mul) => lhs.checked_mul(rhs),
+        (Arithmetic::Checked, Op::Div) => lhs.checked_div(rhs),
+        (Arithmetic::Wrapping, Op::Add) => Some(lhs.wrapping_add(rhs)),
+        (Arithmetic::Wrapping, Op::Sub) => Some(lhs.wrapping_sub(rhs)),
+        (Arithmetic::Wrapping, Op::Mul) => Some(lhs.wrapping_mul(rhs)),
+        (Arithmetic::Wrapping, Op::Div) => Some(lhs.wrapping_div(rhs)),
+        (Arithmetic::Saturating, Op::Add) => Some(lhs.saturating_add(rhs)),
+        (Arithmetic::Saturating, Op::Sub) => Some(lhs.saturating_sub(rhs)),
+        (Arithmetic::Saturating, Op::Mul) => Some(lhs.saturating_mul(rhs)),
+        (Arithmetic::Saturating, Op::Div) => Some(lhs.saturating_div(rhs)),
+        (_, _) => None,
+    }
+}
+
+/// Returns `None` when dividing by zero or for non-arithmetic operators.
+#[cfg(feature = "bigint")]
+pub(crate) fn apply(op: &Op, lhs: Int, rhs: Int, _arithmetic: Arithmetic) -> Option<Int> {
+    use num_traits::Zero;
+
+    match op {
+        Op::Add => Some(lhs + rhs),
+        Op::Sub => Some(lhs - rhs),
+        Op::Mul => Some(lhs * rhs),
+        Op::Div if rhs.is_zero() => None,
+        Op::Div => Some(lhs / rhs),
+        _ => None,
+    }
+}
+
+#[cfg(not(feature = "bigint"))]
+pub(crate) fn neg(n: Int, arithmetic: Arithmetic) -> Option<Int> {
+    match arithmetic {
+        Arithmetic::Checked => n.checked_neg(),
+        Arithmetic::Wrapping => Some(n.wrapping_neg()),
+        Arithmetic::Saturating => Some(n.saturating_neg()),
+    }
+}
+
+#[cfg(feature = "bigint")]
+pub(crate) fn neg(n: Int, _arithmetic: Arithmetic) -> Option<Int> {
+    Some(-n)
+}
+
+#[cfg(not(feature = "bigint"))]
+pub(crate) fn to_f64(n: &Int) -> f64 {
+    *n as f64
+}
+
+#[cfg(feature = "bigint")]
+pub(crate) fn to_f64(n: &Int) -> f64 {
+    use num_traits::ToPrimitive;
+
+    n.to_f64().unwrap_or(f64::NAN)
+}
+
+/// Truncates towards zero. Returns `None` for NaN, infinities and, without
+/// the `bigint` feature, values out of range.
+#[cfg(not(feature = "bigint"))]
+pub(crate) fn from_f64(f: f64) -> Option<Int> {
+    // 2^63 is exact as a float, unlike `i64::MAX`.
+    const LIMIT: f64 = 9_223_372_036_854_775_808.0;
+
+    let truncated = f.trunc();
+    (-LIMIT..LIMIT)
+        .contains(&truncated)
+        .then_some(truncated as Int)
+}
+
+#[cfg(feature = "bigint")]
+pub(crate) fn from_f64(f: f64) -> Option<Int> {
+    use num_traits::FromPrimitive;
+
+    Int::from_f64(f.trunc())
+}
+
+/// Copies `n`, which is cheap unless it is a big integer.
+#[allow(clippy::clone_on_copy)]
+pub(crate) fn duplicate(n: &Int) -> Int {
+    n.clone()
+}
+
+/// Raises `base` to a non-negative `exp`, or returns `None` if the result
+/// does not fit.
+pub(crate) fn pow(mut base: Int, mut exp: Int) -> Option<Int> {
+    let (zero, one, two) = (Int::from(0), Int::from(1), Int::from(2));
+    let mut result = Int::from(1);
+
+    while exp > zero {
+        if duplicate(&exp) % duplicate(&two) == one {
+            result = apply(&Op::Mul, result, duplicate(&base), Arithmetic::Checked)?;
+        }
+        exp /= &two;
+        if exp > zero {
+            base = apply(&Op::Mul, duplicate(&base), base, Arithmetic::Checked)?;
+        }
+    }
+
+    Some(result)
+}
+
+/// The remainder of dividing by `rhs`, which is never negative. Returns
+/// `None` if `rhs` is zero or the result does not fit.
+#[cfg(not(feature = "bigint"))]
+pub(crate) fn rem_euclid(lhs: Int, rhs: Int) -> Option<Int> {
+    lhs.checked_rem_euclid(rhs)
+}
+
+#[cfg(feature = "bigint")]
+pub(crate) fn rem_euclid(lhs: Int, rhs: Int) -> Option<Int> {
+    use num_traits::{Signed, Zero};
+
+    if rhs.is_zero() {
+        return None;
+    }
+
+    let rem = lhs % &rhs;
+    Some(if rem.is_negative() {
+        rem + rhs.abs()
+    } else {
+        rem
+    })
+}
+
+/// Returns `None` for negative numbers and ones too large to index with.
+pub(crate) fn to_usize(n: &Int) -> Option<usize> {
+    usize::try_from(duplicate(n)).ok()
+}
+
+#[cfg(not(feature = "bigint"))]
+pub(crate) fn from_usize(n: usize) -> Int {
+    Int::try_from(n).expect("lengths fit in an integer")
+}
+
+#[cfg(feature = "bigint")]
+pub(crate) fn from_usize(n: usize) -> Int {
+    Int::from(n)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::eval;
+
+    #[test]
+    fn eval_beyond_32_bits() {
+        assert_eq!(
+            eval("2147483647 * 4").map(|val| val.to_string()),
+            Ok("8589934588".to_string()),
+        );
+    }
+
+    #[cfg(not(feature = "bigint"))]
+    #[test]
+    fn eval_beyond_64_bits_overflows() {
+        assert!(matches!(
+            eval("9223372036854775807 + 1"),
+            Err(crate::EvalError::Overflow { .. }),
+        ));
+    }
+
+    #[cfg(feature = "bigint")]
+    #[test]
+    fn eval_beyond_64_bits() {
+        assert_eq!(
+            eval("9223372036854775807 * 9223372036854775807 + 1").map(|val| val.to_string()),
+            Ok("85070591730234615847396907784232501250".to_string()),
+        );
+    }
+
+    #[cfg(feature = "bigint")]
+    #[test]
+    fn eval_literal_beyond_64_bits() {
+        assert_eq!(
+            eval("-123456789012345678901234567890 / 10").map(|val| val.to_string()),
+            Ok("-12345678901234567890123456789".to_string()),
+        );
+    }
+}
@@ -0,0 +1,412 @@
+use crate::error::{ParseError, Span};
+use crate::utils::{Input, OrParse};
+
+/// Words that can never be used as identifiers, so `let if = 1` is rejected
+/// while `letter` is an ordinary name.
+const KEYWORDS: &[&str] = &[
+    "break", "continue", "else", "false", "fn", "for", "if", "in", "let", "loop", "true", "while",
+];
+
+/// Two-character punctuation comes first so that `==` is one token rather
+/// than two `=`.
+const PUNCTUATION: &[&str] = &[
+    "==", "!=", "<=", ">=", "&&", "||", "+=", "-=", "*=", "/=", "=>", "+", "-", "*", "/", "<", ">",
+    "!", "=", "(", ")", "{", "}", "[", "]", ",", ":", ";", ".", "|",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenKind {
+    Ident,
+    Keyword,
+    Number,
+    /// A string literal, holding its contents with escapes resolved.
+    Str(String),
+    Punct,
+    Newline,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token<'a> {
+    pub kind: TokenKind,
+    /// The token as it appears in the source.
+    pub text: &'a str,
+    pub span: Span,
+}
+
+/// Splits `source` into tokens, dropping the spaces between them.
+pub fn tokenize(source: &str) -> Result<Vec<Token<'_>>, ParseError> {
+    let mut s = Input::from(source);
+    let mut tokens = Vec::new();
+
+    loop {
+        (s, _) = skip_spaces(s);
+        if s.is_empty() {
+            return Ok(tokens);
+        }
+
+        let (rest, token) = next_token(s)?;
+        tokens.push(token);
+        s = rest;
+    }
+}
+
+/// Skips the spaces, tabs and comments that may separate two tokens on the
+/// same line. A line comment stops short of its newline, which is still a
+/// token. An unterminated block comment is left for `next_token` to report.
+pub(crate) fn skip_spaces(s: Input<'_>) -> (Input<'_>, &str) {
+    let mut rest = s;
+    loop {
+        let (after_spaces, _) = take_while(|c| c == ' ' || c == '\t', rest);
+        rest = if let Ok(comment) =
+            prefix("#", after_spaces).or_else(|_| prefix("//", after_spaces))
+        {
+            take_while(|c| c != '\n' && c != '\r', comment).0
+        } else if let Ok(after_comment) = block_comment(after_spaces) {
+            after_comment
+        } else {
+            return (after_spaces, s.text_to(after_spaces));
+        };
+    }
+}
+
+/// Skips a `/* */` comment, which may span lines and contain other block
+/// comments.
+fn block_comment(s: Input<'_>) -> Result<Input<'_>, ParseError> {
+    let mut s = prefix("/*", s)?;
+    let mut depth = 1;
+
+    while depth > 0 {
+        if let Ok(rest) = prefix("*/", s) {
+            depth -= 1;
+            s = rest;
+        } else if let Ok(rest) = prefix("/*", s) {
+            depth += 1;
+            s = rest;
+        } else if let Some(c) = s.as_str().chars().next() {
+            s = s.split_at(c.len_utf8()).0;
+        } else {
+            return Err(ParseError::new(s, "`*/`"));
+        }
+    }
+
+    Ok(s)
+}
+
+/// Lexes the token starting exactly at `s`.
+pub(crate) fn next_token(s: Input<'_>) -> Result<(Input<'_>, Token<'_>), ParseError> {
+    let (rest, kind) = match s.as_str().chars().next() {
+        Some('\n') => (s.split_at(1).0, TokenKind::Newline),
+        Some('\r') if s.as_str().starts_with("\r\n") => (s.split_at(2).0, TokenKind::Newline),
+        Some('"') => {
+            let (rest, string) = lex_string(s)?;
+            (rest, TokenKind::Str(string))
+        }
+        Some(c) if c.is_ascii_digit() => (lex_number(s), TokenKind::Number),
+        Some(c) if c.is_ascii_alphabetic() => {
+            let (rest, word) = take_while(|c| c.is_ascii_alphanumeric(), s);
+            if KEYWORDS.contains(&word) {
+                (rest, TokenKind::Keyword)
+            } else {
+                (rest, TokenKind::Ident)
+            }
+        }
+        // Parsers looking for a token right after another one do not skip
+        // spaces first, so a comment can end up here. What follows it counts
+        // as the token, as though the comment were not there.
+        _ if s.as_str().starts_with("/*") => {
+            let after_comment = block_comment(s)?;
+            return next_token(after_comment).map_err(|error| {
+                if error.span.start == after_comment.offset() {
+                    ParseError::new(s, "a token")
+                } else {
+                    error
+                }
+            });
+        }
+        _ => {
+            let punct = PUNCTUATION
+                .iter()
+                .find(|punct| s.as_str().starts_with(*punct))
+                .ok_or_else(|| ParseError::new(s, "a token"))?;
+            (s.split_at(punct.len()).0, TokenKind::Punct)
+        }
+    };
+
+    let token = Token {
+        kind,
+        text: s.text_to(rest),
+        span: s.span_to(rest),
+    };
+    Ok((rest, token))
+}
+
+/// Digits, followed by a fraction and an exponent. A `.` or an `e` only
+/// belongs to the number if digits follow it, so `1.` is `1` then `.`.
+fn lex_number(s: Input<'_>) -> Input<'_> {
+    let digits = |s| take_while1(|c| c.is_ascii_digit(), s, "digits").map(|(s, _)| s);
+
+    let (after_int, _) = take_while(|c| c.is_ascii_digit(), s);
+    let after_fraction = prefix(".", after_int).and_then(digits).unwrap_or(after_int);
+    prefix("e", after_fraction)
+        .or_else(|_| prefix("E", after_fraction))
+        .map(|s| prefix("-", s).or_else(|_| prefix("+", s)).unwrap_or(s))
+        .and_then(digits)
+        .unwrap_or(after_fraction)
+}
+
+/// Lexes a double-quoted string literal and resolves its escape sequences.
+fn lex_string(s: Input<'_>) -> Result<(Input<'_>, String), ParseError> {
+    let mut s = prefix("\"", s)?;
+    let mut string = String::new();
+
+    loop {
+        match s.as_str().chars().next() {
+            None => return Err(ParseError::new(s, "`\"`")),
+            Some('"') => return Ok((s.split_at(1).0, string)),
+            Some('\\') => {
+                let (rest, escaped) = lex_escape(s)?;
+                string.push(escaped);
+                s = rest;
+            }
+            Some(c) => {
+                string.push(c);
+                s = s.split_at(c.len_utf8()).0;
+            }
+        }
+    }
+}
+
+fn lex_escape(s: Input<'_>) -> Result<(Input<'_>, char), ParseError> {
+    let s = prefix("\\", s)?;
+
+    let simple = |escape, c| prefix(escape, s).map(|s| (s, c));
+    simple("n", '\n')
+        .or_parse(|| simple("t", '\t'))
+        .or_parse(|| simple("\"", '"'))
+        .or_parse(|| simple("\\", '\\'))
+        .or_parse(|| {
+            let digits_start = prefix("u{", s)?;
+            let (s, digits) = take_while1(
+                |c| c.is_ascii_hexdigit(),
+                digits_start,
+                "hexadecimal digits",
+            )?;
+            let rest = prefix("}", s)?;
+
+            u32::from_str_radix(digits, 16)
+                .ok()
+                .and_then(char::from_u32)
+                .map(|c| (rest, c))
+                .ok_or_else(|| ParseError {
+                    span: digits_start.span_to(s),
+                    ..ParseError::new(digits_start, "a valid unicode code point")
+                })
+        })
+}
+
+fn prefix<'a>(prefix: &str, s: Input<'a>) -> Result<Input<'a>, ParseError> {
+    if s.as_str().starts_with(prefix) {
+        Ok(s.split_at(prefix.len()).0)
+    } else {
+        Err(ParseError::new(s, format!("`{}`", prefix)))
+    }
+}
+
+fn take_while(accept: impl Fn(char) -> bool, s: Input<'_>) -> (Input<'_>, &str) {
+    let take_end = s
+        .as_str()
+        .char_indices()
+        .find_map(|(idx, c)| if accept(c) { None } else { Some(idx) })
+        .unwrap_or(s.as_str().len());
+    s.split_at(take_end)
+}
+
+fn take_while1<'a>(
+    accept: impl Fn(char) -> bool,
+    s: Input<'a>,
+    expected: &str,
+) -> Result<(Input<'a>, &'a str), ParseError> {
+    let (remainder, extracted) = take_while(accept, s);
+
+    if extracted.is_empty() {
+        Err(ParseError::new(s, expected))
+    } else {
+        Ok((remainder, extracted))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(source: &str) -> Vec<(TokenKind, &str)> {
+        tokenize(source)
+            .unwrap()
+            .into_iter()
+            .map(|token| (token.kind, token.text))
+            .collect()
+    }
+
+    #[test]
+    fn lex_binding_def() {
+        assert_eq!(
+            tokenize("let a = 10"),
+            Ok(vec![
+                Token {
+                    kind: TokenKind::Keyword,
+                    text: "let",
+                    span: Span::new(0, 3),
+                },
+                Token {
+                    kind: TokenKind::Ident,
+                    text: "a",
+                    span: Span::new(4, 5),
+                },
+                Token {
+                    kind: TokenKind::Punct,
+                    text: "=",
+                    span: Span::new(6, 7),
+                },
+                Token {
+                    kind: TokenKind::Number,
+                    text: "10",
+                    span: Span::new(8, 10),
+                },
+            ]),
+        );
+    }
+
+    #[test]
+    fn lex_keyword_prefix_as_identifier() {
+        assert_eq!(
+            kinds("letter iffy fn"),
+            vec![
+                (TokenKind::Ident, "letter"),
+                (TokenKind::Ident, "iffy"),
+                (TokenKind::Keyword, "fn"),
+            ],
+        );
+    }
+
+    #[test]
+    fn lex_longest_punctuation() {
+        assert_eq!(
+            kinds("a==b=c||d|"),
+            vec![
+                (TokenKind::Ident, "a"),
+                (TokenKind::Punct, "=="),
+                (TokenKind::Ident, "b"),
+                (TokenKind::Punct, "="),
+                (TokenKind::Ident, "c"),
+                (TokenKind::Punct, "||"),
+                (TokenKind::Ident, "d"),
+                (TokenKind::Punct, "|"),
+            ],
+        );
+    }
+
+    #[test]
+    fn lex_numbers() {
+        assert_eq!(
+            kinds("12 2.5e-3 1.x 3e"),
+            vec![
+                (TokenKind::Number, "12"),
+                (TokenKind::Number, "2.5e-3"),
+                (TokenKind::Number, "1"),
+                (TokenKind::Punct, "."),
+                (TokenKind::Ident, "x"),
+                (TokenKind::Number, "3"),
+                (TokenKind::Ident, "e"),
+            ],
+        );
+    }
+
+    #[test]
+    fn lex_string_and_newline() {
+        assert_eq!(
+            kinds("\"a\\tb\"\n1"),
+            vec![
+                (TokenKind::Str("a\tb".to_string()), "\"a\\tb\""),
+                (TokenKind::Newline, "\n"),
+                (TokenKind::Number, "1"),
+            ],
+        );
+    }
+
+    #[test]
+    fn lex_without_comments() {
+        assert_eq!(
+            kinds("1 # one\n\t2 // two\r\n3 /* a /* nested */ comment */ / 4"),
+            vec![
+                (TokenKind::Number, "1"),
+                (TokenKind::Newline, "\n"),
+                (TokenKind::Number, "2"),
+                (TokenKind::Newline, "\r\n"),
+                (TokenKind::Number, "3"),
+                (TokenKind::Punct, "/"),
+                (TokenKind::Number, "4"),
+            ],
+        );
+    }
+
+    #[test]
+    fn lex_block_comment_across_lines() {
+        assert_eq!(
+            kinds("1 /* a\nb */ 2"),
+            vec![(TokenKind::Number, "1"), (TokenKind::Number, "2")]
+        );
+    }
+
+    #[test]
+    fn lex_unterminated_block_comment() {
+        assert_eq!(
+            tokenize("1 /* a /* b */"),
+            Err(ParseError {
+                span: Span::new(14, 14),
+                line: 1,
+                column: 15,
+                expected: vec!["`*/`".to_string()],
+            }),
+        );
+    }
+
+    #[test]
+    fn lex_token_after_closed_block_comment() {
+        let (_, token) = next_token("/* a *//* b */[0]".into()).unwrap();
+
+        assert_eq!(
+            token,
+            Token {
+                kind: TokenKind::Punct,
+                text: "[",
+                span: Span::new(14, 15),
+            },
+        );
+    }
+
+    #[test]
+    fn lex_nothing_after_closed_block_comment() {
+        assert_eq!(
+            next_token("/* a */".into()),
+            Err(ParseError {
+                span: Span::new(0, 1),
+                line: 1,
+                column: 1,
+                expected: vec!["a token".to_string()],
+            }),
+        );
+    }
+
+    #[test]
+    fn lex_unknown_character() {
+        assert_eq!(
+            tokenize("1 @"),
+            Err(ParseError {
+                span: Span::new(2, 3),
+                line: 1,
+                column: 3,
+                expected: vec!["a token".to_string()],
+            }),
+        );
+    }
+}
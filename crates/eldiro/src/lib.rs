@@ -1,29 +1,45 @@
+mod assignment;
 mod binding_def;
+mod closure;
 mod env;
+mod error;
 mod expr;
 mod func_def;
+mod int;
+mod lexer;
+mod native;
+mod prelude;
+mod program;
+mod signal;
 mod stmt;
 mod utils;
 mod val;
 
-pub use env::Env;
+pub use closure::Closure;
+pub use env::{Arithmetic, Env};
+pub use error::{ConversionError, EvalError, ParseError, Span};
+pub use int::Int;
+pub use lexer::{tokenize, Token, TokenKind};
+pub use native::NativeFunc;
+pub use program::Program;
 pub use val::Val;
 
-#[derive(Debug)]
-pub struct Parse(stmt::Stmt);
-
-impl Parse {
-    pub fn eval(&self, env: &mut Env) -> Result<Val, String> {
-        self.0.eval(env)
-    }
+/// Parses a whole program, such as the contents of a script file.
+pub fn parse(s: &str) -> Result<Program, ParseError> {
+    let (_, program) = Program::new(utils::Input::new_source(s))?;
+    Ok(program)
 }
 
-pub fn parse(s: &str) -> Result<Parse, String> {
-    let (s, stmt) = stmt::Stmt::new(s)?;
+/// Parses and evaluates `s` in `env`. Shared by the unit tests, which parse
+/// without a source id so that spans compare equal to [`Span::new`].
+#[cfg(test)]
+fn eval_in(env: &mut Env, s: &str) -> Result<Val, EvalError> {
+    let (_, program) = Program::new(s.into()).unwrap();
+    program.eval(env)
+}
 
-    if s.is_empty() {
-        Ok(Parse(stmt))
-    } else {
-        Err("input was not consumed fully by parser".to_string())
-    }
+/// Like [`eval_in`], in a fresh environment with the prelude.
+#[cfg(test)]
+fn eval(s: &str) -> Result<Val, EvalError> {
+    eval_in(&mut Env::default(), s)
 }
@@ -0,0 +1,114 @@
+use std::fmt;
+
+use crate::error::{EvalError, Span};
+use crate::val::Val;
+
+type NativeFn = dyn Fn(&[Val], Span) -> Result<Val, EvalError>;
+
+/// A function implemented in Rust. It is called like any other function, with
+/// the number of arguments checked before it runs.
+pub struct NativeFunc {
+    pub(crate) name: String,
+    pub(crate) arity: usize,
+    pub(crate) func: Box<NativeFn>,
+}
+
+impl NativeFunc {
+    pub(crate) fn call(&self, args: &[Val], span: Span) -> Result<Val, EvalError> {
+        (self.func)(args, span)
+    }
+}
+
+// Rust closures cannot be compared, so native functions are equal only to
+// themselves.
+impl PartialEq for NativeFunc {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl fmt::Debug for NativeFunc {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NativeFunc")
+            .field("name", &self.name)
+            .field("arity", &self.arity)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::env::Env;
+    use crate::eval_in;
+    use crate::int::Int;
+
+    fn env_with_clock() -> Env {
+        let mut env = Env::default();
+        env.register_fn("now", 0, |_| Ok::<_, String>(Val::from(1_700_000_000)));
+        env.register_fn("later", 1, |args| match &args[0] {
+            Val::Number(n) if *n >= Int::from(0) => Ok(Val::Number(Int::from(1_700_000_000) + n)),
+            _ => Err("expected a non-negative number of seconds"),
+        });
+        env
+    }
+
+    #[test]
+    fn eval_registered_fn() {
+        let mut env = env_with_clock();
+
+        assert_eq!(eval_in(&mut env, "now"), Ok(Val::from(1_700_000_000)));
+        assert_eq!(eval_in(&mut env, "later 5"), Ok(Val::from(1_700_000_005)));
+        assert_eq!(
+            eval_in(&mut env, "(|f| f 10) later"),
+            Ok(Val::from(1_700_000_010)),
+        );
+    }
+
+    #[test]
+    fn eval_registered_fn_with_wrong_number_of_arguments() {
+        assert_eq!(
+            eval_in(&mut env_with_clock(), "later 1 2"),
+            Err(EvalError::ArityMismatch {
+                name: "later".to_string(),
+                expected: 1,
+                got: 2,
+                span: Span::new(0, 9),
+            }),
+        );
+    }
+
+    #[test]
+    fn eval_registered_fn_returning_error() {
+        let error = eval_in(&mut env_with_clock(), "later (-1)").unwrap_err();
+
+        assert_eq!(
+            error,
+            EvalError::Native {
+                name: "later".to_string(),
+                message: "expected a non-negative number of seconds".to_string(),
+                span: Span::new(0, 10),
+            },
+        );
+        assert_eq!(
+            error.to_string(),
+            "'later' failed: expected a non-negative number of seconds",
+        );
+    }
+
+    #[test]
+    fn eval_registered_fn_shadowed_by_script() {
+        let mut env = env_with_clock();
+        eval_in(&mut env, "fn now => 42").unwrap();
+
+        assert_eq!(eval_in(&mut env, "now"), Ok(Val::from(42)));
+    }
+
+    #[test]
+    fn eval_registered_fn_from_script_function() {
+        let mut env = env_with_clock();
+        eval_in(&mut env, "fn elapsed => now - 1699999999").unwrap();
+
+        assert_eq!(eval_in(&mut env, "elapsed"), Ok(Val::from(1)));
+    }
+}
@@ -0,0 +1,16 @@
+//! The functions every [`Env::default`] starts out with. An environment
+//! created with [`Env::without_prelude`] has none of them.
+
+use crate::env::Env;
+
+mod collections;
+mod math;
+mod strings;
+mod types;
+
+pub(crate) fn register(env: &mut Env) {
+    math::register(env);
+    strings::register(env);
+    collections::register(env);
+    types::register(env);
+}
@@ -0,0 +1,144 @@
+use crate::env::{Arithmetic, Env};
+use crate::expr::Op;
+use crate::int::{self, Int};
+use crate::val::Val;
+
+// Lists and maps are values, so `push`, `concat`, `insert` and `reverse` return
+// new ones rather than changing their arguments.
+pub(super) fn register(env: &mut Env) {
+    // Strings are measured in characters.
+    env.store_native("len", 1, |args, span| {
+        let len = match args[0].clone() {
+            Val::Str(s) => s.chars().count(),
+            Val::Map(entries) => entries.len(),
+            other => other.expect_list(span)?.len(),
+        };
+        Ok(Val::Number(int::from_usize(len)))
+    });
+
+    env.store_native("push", 2, |args, span| {
+        let mut items = args[0].clone().expect_list(span)?;
+        items.push(args[1].clone());
+        Ok(Val::List(items))
+    });
+
+    env.store_native("concat", 2, |args, span| {
+        let mut items = args[0].clone().expect_list(span)?;
+        items.extend(args[1].clone().expect_list(span)?);
+        Ok(Val::List(items))
+    });
+
+    env.store_native("reverse", 1, |args, span| {
+        let mut items = args[0].clone().expect_list(span)?;
+        items.reverse();
+        Ok(Val::List(items))
+    });
+
+    // Looks for an element of a list, a key of a map or a substring.
+    env.store_native("contains", 2, |args, span| {
+        let found = match args[0].clone() {
+            Val::Str(s) => s.contains(&args[1].clone().expect_str(span)?),
+            Val::Map(entries) => entries.contains_key(&args[1].clone().expect_str(span)?),
+            other => other.expect_list(span)?.contains(&args[1]),
+        };
+        Ok(Val::Bool(found))
+    });
+
+    // The integers from `start` up to but not including `end`.
+    env.store_native("range", 2, |args, span| {
+        let mut n = args[0].clone().expect_int(span)?;
+        let end = args[1].clone().expect_int(span)?;
+
+        let mut items = Vec::new();
+        while n < end {
+            items.push(Val::Number(int::duplicate(&n)));
+            n = int::apply(&Op::Add, n, Int::from(1), Arithmetic::Checked)
+                .expect("less than `end`, so adding one fits");
+        }
+        Ok(Val::List(items))
+    });
+
+    env.store_native("insert", 3, |args, span| {
+        let mut entries = args[0].clone().expect_map(span)?;
+        let key = args[1].clone().expect_str(span)?;
+        entries.insert(key, args[2].clone());
+        Ok(Val::Map(entries))
+    });
+
+    env.store_native("keys", 1, |args, span| {
+        let entries = args[0].clone().expect_map(span)?;
+        Ok(Val::List(entries.into_keys().map(Val::Str).collect()))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::env::Env;
+    use crate::error::{EvalError, Span};
+    use crate::eval;
+    use crate::val::Val;
+
+    #[test]
+    fn eval_len() {
+        assert_eq!(eval("len [1, 2, 3]"), Ok(Val::from(3)));
+        assert_eq!(eval("len []"), Ok(Val::from(0)));
+        assert_eq!(eval(r#"len { "a": 1, "b": 2 }"#), Ok(Val::from(2)));
+        assert_eq!(eval(r#"len "héj""#), Ok(Val::from(3)));
+    }
+
+    #[test]
+    fn eval_push_returns_new_list() {
+        let mut env = Env::default();
+        for line in ["let xs = [1]", "let ys = push xs 2"] {
+            crate::parse(line).unwrap().eval(&mut env).unwrap();
+        }
+
+        assert_eq!(env.get_binding("xs"), Some(Val::from(vec![1])));
+        assert_eq!(env.get_binding("ys"), Some(Val::from(vec![1, 2])));
+    }
+
+    #[test]
+    fn eval_concat() {
+        assert_eq!(eval("concat [1] [2, 3]"), Ok(Val::from(vec![1, 2, 3])));
+    }
+
+    #[test]
+    fn eval_concat_with_non_list() {
+        assert_eq!(
+            eval("concat [1] 2"),
+            Err(EvalError::TypeMismatch {
+                expected: "list",
+                found: "number",
+                span: Span::new(0, 12),
+            }),
+        );
+    }
+
+    #[test]
+    fn eval_reverse() {
+        assert_eq!(eval("reverse [1, 2, 3]"), Ok(Val::from(vec![3, 2, 1])));
+    }
+
+    #[test]
+    fn eval_contains() {
+        assert_eq!(eval("contains [1, 2] 2"), Ok(Val::Bool(true)));
+        assert_eq!(eval("contains [1, 2] 3"), Ok(Val::Bool(false)));
+        assert_eq!(eval(r#"contains { "a": 1 } "a""#), Ok(Val::Bool(true)));
+        assert_eq!(eval(r#"contains "haystack" "st""#), Ok(Val::Bool(true)));
+    }
+
+    #[test]
+    fn eval_range() {
+        assert_eq!(eval("range (-1) 3"), Ok(Val::from(vec![-1, 0, 1, 2])));
+        assert_eq!(eval("range 3 1"), Ok(Val::from(Vec::<Val>::new())));
+    }
+
+    #[test]
+    fn eval_insert_and_keys() {
+        assert_eq!(
+            eval(r#"keys (insert { "b": 1 } "a" 2)"#),
+            Ok(Val::from(vec!["a", "b"])),
+        );
+        assert_eq!(eval(r#"(insert { "a": 1 } "a" 2).a"#), Ok(Val::from(2)));
+    }
+}
@@ -0,0 +1,157 @@
+use crate::env::{Arithmetic, Env};
+use crate::error::EvalError;
+use crate::int::{self, Int};
+use crate::val::{Num, Val};
+
+pub(super) fn register(env: &mut Env) {
+    env.store_native("abs", 1, |args, span| {
+        match args[0].clone().expect_num(span)? {
+            Num::Int(n) if n < Int::from(0) => int::neg(n, Arithmetic::Checked)
+                .map(Val::Number)
+                .ok_or(EvalError::Overflow { span }),
+            Num::Int(n) => Ok(Val::Number(n)),
+            Num::Float(f) => Ok(Val::Float(f.abs())),
+        }
+    });
+
+    // Both return one of their arguments unchanged, so `min 1 2.0` is the
+    // integer `1`.
+    env.store_native("min", 2, |args, span| {
+        let (a, b) = (args[0].clone(), args[1].clone());
+        Ok(
+            if b.clone().expect_num(span)? < a.clone().expect_num(span)? {
+                b
+            } else {
+                a
+            },
+        )
+    });
+
+    env.store_native("max", 2, |args, span| {
+        let (a, b) = (args[0].clone(), args[1].clone());
+        Ok(
+            if b.clone().expect_num(span)? > a.clone().expect_num(span)? {
+                b
+            } else {
+                a
+            },
+        )
+    });
+
+    // An integer raised to a non-negative integer stays an integer; anything
+    // else is computed with floats.
+    env.store_native("pow", 2, |args, span| {
+        let base = args[0].clone().expect_num(span)?;
+        let exp = args[1].clone().expect_num(span)?;
+
+        match (base, exp) {
+            (Num::Int(base), Num::Int(exp)) if exp >= Int::from(0) => int::pow(base, exp)
+                .map(Val::Number)
+                .ok_or(EvalError::Overflow { span }),
+            (base, exp) => Ok(Val::Float(base.as_f64().powf(exp.as_f64()))),
+        }
+    });
+
+    // Unlike a plain remainder, the result is never negative, so `mod (-1) 3`
+    // is `2`.
+    env.store_native("mod", 2, |args, span| {
+        let lhs = args[0].clone().expect_num(span)?;
+        let rhs = args[1].clone().expect_num(span)?;
+
+        match (lhs, rhs) {
+            (Num::Int(_), Num::Int(rhs)) if rhs == Int::from(0) => {
+                Err(EvalError::DivisionByZero { span })
+            }
+            (Num::Int(lhs), Num::Int(rhs)) => int::rem_euclid(lhs, rhs)
+                .map(Val::Number)
+                .ok_or(EvalError::Overflow { span }),
+            (lhs, rhs) => Ok(Val::Float(lhs.as_f64().rem_euclid(rhs.as_f64()))),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::{EvalError, Span};
+    use crate::eval;
+    use crate::val::Val;
+
+    #[test]
+    fn eval_abs() {
+        assert_eq!(eval("abs (-3)"), Ok(Val::from(3)));
+        assert_eq!(eval("abs 3"), Ok(Val::from(3)));
+        assert_eq!(eval("abs (-2.5)"), Ok(Val::from(2.5)));
+    }
+
+    #[cfg(not(feature = "bigint"))]
+    #[test]
+    fn eval_abs_of_smallest_integer() {
+        assert_eq!(
+            eval("abs (-9223372036854775807 - 1)"),
+            Err(EvalError::Overflow {
+                span: Span::new(0, 30),
+            }),
+        );
+    }
+
+    #[test]
+    fn eval_min_and_max() {
+        assert_eq!(eval("min 3 (-1)"), Ok(Val::from(-1)));
+        assert_eq!(eval("max 3 (-1)"), Ok(Val::from(3)));
+        assert_eq!(eval("min 1 2.0"), Ok(Val::from(1)));
+        assert_eq!(eval("max 1 2.0"), Ok(Val::from(2.0)));
+    }
+
+    #[test]
+    fn eval_min_of_non_number() {
+        assert_eq!(
+            eval("min 1 \"a\""),
+            Err(EvalError::TypeMismatch {
+                expected: "number",
+                found: "string",
+                span: Span::new(0, 9),
+            }),
+        );
+    }
+
+    #[test]
+    fn eval_pow() {
+        assert_eq!(eval("pow 2 10"), Ok(Val::from(1024)));
+        assert_eq!(eval("pow 7 0"), Ok(Val::from(1)));
+        assert_eq!(eval("pow 2 (-1)"), Ok(Val::from(0.5)));
+        assert_eq!(eval("pow 4.0 0.5"), Ok(Val::from(2.0)));
+    }
+
+    #[cfg(not(feature = "bigint"))]
+    #[test]
+    fn eval_overflowing_pow() {
+        assert_eq!(
+            eval("pow 2 63"),
+            Err(EvalError::Overflow {
+                span: Span::new(0, 8),
+            }),
+        );
+        assert_eq!(
+            eval("pow 2 62").map(|val| val.to_string()),
+            Ok("4611686018427387904".to_string())
+        );
+    }
+
+    #[test]
+    fn eval_mod() {
+        assert_eq!(eval("mod 7 3"), Ok(Val::from(1)));
+        assert_eq!(eval("mod (-1) 3"), Ok(Val::from(2)));
+        assert_eq!(eval("mod 7 (-3)"), Ok(Val::from(1)));
+        assert_eq!(eval("mod 5.5 2"), Ok(Val::from(1.5)));
+    }
+
+    #[test]
+    fn eval_mod_by_zero() {
+        assert_eq!(
+            eval("mod 1 0"),
+            Err(EvalError::DivisionByZero {
+                span: Span::new(0, 7),
+            }),
+        );
+    }
+}
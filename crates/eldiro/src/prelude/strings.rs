@@ -0,0 +1,73 @@
+use strsplit::StrSplit;
+
+use crate::env::Env;
+use crate::val::Val;
+
+pub(super) fn register(env: &mut Env) {
+    env.store_native("upper", 1, |args, span| {
+        Ok(Val::Str(args[0].clone().expect_str(span)?.to_uppercase()))
+    });
+
+    env.store_native("lower", 1, |args, span| {
+        Ok(Val::Str(args[0].clone().expect_str(span)?.to_lowercase()))
+    });
+
+    // An empty delimiter splits the string into its characters.
+    env.store_native("split", 2, |args, span| {
+        let s = args[0].clone().expect_str(span)?;
+        let delimiter = args[1].clone().expect_str(span)?;
+
+        let parts = if delimiter.is_empty() {
+            s.chars().map(|c| Val::Str(c.to_string())).collect()
+        } else {
+            StrSplit::new(&s, delimiter.as_str())
+                .map(|part| Val::Str(part.to_string()))
+                .collect()
+        };
+
+        Ok(Val::List(parts))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::{EvalError, Span};
+    use crate::eval;
+    use crate::val::Val;
+
+    #[test]
+    fn eval_upper_and_lower() {
+        assert_eq!(eval(r#"upper "Straße""#), Ok(Val::from("STRASSE")));
+        assert_eq!(eval(r#"lower "MiXed""#), Ok(Val::from("mixed")));
+    }
+
+    #[test]
+    fn eval_upper_of_non_string() {
+        assert_eq!(
+            eval("upper 1"),
+            Err(EvalError::TypeMismatch {
+                expected: "string",
+                found: "number",
+                span: Span::new(0, 7),
+            }),
+        );
+    }
+
+    #[test]
+    fn eval_split() {
+        assert_eq!(
+            eval(r#"split "a, b,, c" ", ""#),
+            Ok(Val::from(vec!["a", "b,", "c"])),
+        );
+        assert_eq!(eval(r#"split "abc" ",""#), Ok(Val::from(vec!["abc"])));
+        assert_eq!(eval(r#"split "a," ",""#), Ok(Val::from(vec!["a", ""])));
+    }
+
+    #[test]
+    fn eval_split_on_empty_delimiter() {
+        assert_eq!(
+            eval(r#"split "héj" """#),
+            Ok(Val::from(vec!["h", "é", "j"])),
+        );
+    }
+}
@@ -0,0 +1,139 @@
+use crate::env::Env;
+use crate::error::EvalError;
+use crate::int;
+use crate::val::{Num, Val};
+
+/// Every type name, each of which gets a predicate such as `isnumber`.
+const TYPE_NAMES: [&str; 8] = [
+    "number", "float", "bool", "string", "list", "map", "function", "unit",
+];
+
+pub(super) fn register(env: &mut Env) {
+    env.store_native("type", 1, |args, _| {
+        Ok(Val::Str(args[0].type_name().to_string()))
+    });
+
+    for type_name in TYPE_NAMES {
+        env.store_native(&format!("is{}", type_name), 1, move |args, _| {
+            Ok(Val::Bool(args[0].type_name() == type_name))
+        });
+    }
+
+    // Truncates towards zero, failing on NaN and infinities.
+    env.store_native("int", 1, |args, span| {
+        match args[0].clone().expect_num(span)? {
+            Num::Int(n) => Ok(Val::Number(n)),
+            Num::Float(f) => int::from_f64(f)
+                .map(Val::Number)
+                .ok_or(EvalError::Overflow { span }),
+        }
+    });
+
+    env.store_native("float", 1, |args, span| {
+        Ok(Val::Float(args[0].clone().expect_num(span)?.as_f64()))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::env::Env;
+    use crate::error::{EvalError, Span};
+    use crate::eval;
+    use crate::val::Val;
+
+    #[test]
+    fn eval_type() {
+        assert_eq!(eval("type 1"), Ok(Val::from("number")));
+        assert_eq!(eval("type [1.5]"), Ok(Val::from("list")));
+        assert_eq!(eval("type type"), Ok(Val::from("function")));
+    }
+
+    #[test]
+    fn eval_type_predicates() {
+        for (source, expected) in [
+            ("isnumber 1", true),
+            ("isnumber 1.0", false),
+            ("isfloat 1.0", true),
+            ("isbool false", true),
+            (r#"isstring "1""#, true),
+            ("islist []", true),
+            (r#"ismap { "a": 1 }"#, true),
+            ("isfunction (|x| x)", true),
+            ("isunit {}", true),
+            ("isunit 0", false),
+        ] {
+            assert_eq!(eval(source), Ok(Val::Bool(expected)), "{}", source);
+        }
+    }
+
+    #[test]
+    fn eval_int_truncates_float() {
+        assert_eq!(eval("int 2.9"), Ok(Val::from(2)));
+        assert_eq!(eval("int (-2.9)"), Ok(Val::from(-2)));
+        assert_eq!(eval("int 7"), Ok(Val::from(7)));
+    }
+
+    #[cfg(not(feature = "bigint"))]
+    #[test]
+    fn eval_int_of_float_out_of_range() {
+        assert_eq!(
+            eval("int 1e19"),
+            Err(EvalError::Overflow {
+                span: Span::new(0, 8),
+            }),
+        );
+    }
+
+    #[test]
+    fn eval_int_of_nan() {
+        assert_eq!(
+            eval("int (0.0 / 0)"),
+            Err(EvalError::Overflow {
+                span: Span::new(0, 13),
+            }),
+        );
+    }
+
+    #[test]
+    fn eval_float_of_number() {
+        assert_eq!(eval("float 3"), Ok(Val::from(3.0)));
+        assert_eq!(eval("float 3 / 2"), Ok(Val::from(1.5)));
+    }
+
+    #[test]
+    fn eval_float_of_bool() {
+        assert_eq!(
+            eval("float true"),
+            Err(EvalError::TypeMismatch {
+                expected: "number",
+                found: "bool",
+                span: Span::new(0, 10),
+            }),
+        );
+    }
+
+    #[test]
+    fn eval_builtin_shadowed_by_definition() {
+        let mut env = Env::default();
+        crate::parse("fn int x => x + 1")
+            .unwrap()
+            .eval(&mut env)
+            .unwrap();
+
+        assert_eq!(
+            crate::parse("int 1").unwrap().eval(&mut env),
+            Ok(Val::from(2))
+        );
+    }
+
+    #[test]
+    fn eval_without_prelude() {
+        assert_eq!(
+            crate::eval_in(&mut Env::without_prelude(), "int 1"),
+            Err(EvalError::UnboundName {
+                name: "int".to_string(),
+                span: Span::new(0, 5),
+            }),
+        );
+    }
+}
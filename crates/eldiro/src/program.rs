@@ -0,0 +1,128 @@
+use crate::env::Env;
+use crate::error::{EvalError, ParseError};
+use crate::signal::Signal;
+use crate::stmt::Stmt;
+use crate::utils::{self, Input};
+use crate::val::Val;
+
+/// A whole source file: statements separated by newlines or `;`. The source
+/// is kept so that errors can be shown in it.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Program {
+    stmts: Vec<Stmt>,
+    source: String,
+    source_id: usize,
+}
+
+impl Program {
+    pub(crate) fn new(s: Input) -> Result<(Input, Self), ParseError> {
+        let (rest, stmts) = utils::separated_until(Stmt::new, None, s)?;
+
+        Ok((
+            rest,
+            Self {
+                stmts,
+                source: s.source().to_string(),
+                source_id: s.source_id(),
+            },
+        ))
+    }
+
+    pub(crate) fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub(crate) fn source_id(&self) -> usize {
+        self.source_id
+    }
+
+    /// Evaluates each statement in turn, giving the value of the last one.
+    pub fn eval(&self, env: &mut Env) -> Result<Val, EvalError> {
+        let stack_base = env.start_stack();
+        let result = self.eval_stmts(env);
+        env.restore_stack(stack_base);
+        result
+    }
+
+    fn eval_stmts(&self, env: &mut Env) -> Result<Val, EvalError> {
+        let mut val = Val::Unit;
+        for stmt in &self.stmts {
+            val = stmt.eval(env).map_err(Signal::into_error)?;
+        }
+        Ok(val)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::Span;
+
+    use super::*;
+    use crate::eval;
+
+    #[test]
+    fn parse_empty_program() {
+        let (rest, program) = Program::new(" # nothing here\n\n".into()).unwrap();
+
+        assert_eq!(rest, "".into());
+        assert!(program.stmts.is_empty());
+    }
+
+    #[test]
+    fn parse_stmts_separated_by_newlines_and_semicolons() {
+        let (_, program) = Program::new("let a = 1; let b = 2\n\n  a ;; b\n".into()).unwrap();
+
+        assert_eq!(program.stmts.len(), 4);
+    }
+
+    #[test]
+    fn parse_stmts_without_separator() {
+        assert_eq!(
+            Program::new("let a = 1 let b = 2".into()).unwrap_err(),
+            ParseError {
+                span: Span::new(10, 11),
+                line: 1,
+                column: 11,
+                expected: vec!["`;`".to_string(), "a newline".to_string()],
+            },
+        );
+    }
+
+    #[test]
+    fn eval_stmts_in_order() {
+        assert_eq!(
+            eval(
+                "
+                let a = 1
+                fn double x => x * 2
+                a = double a; a += 1
+                a
+                "
+            ),
+            Ok(Val::from(3)),
+        );
+    }
+
+    #[test]
+    fn eval_with_block_comments_right_after_tokens() {
+        assert_eq!(eval("let x = 1/*c*/\nx"), Ok(Val::from(1)));
+        assert_eq!(eval("(1)/*c*/ + 1"), Ok(Val::from(2)));
+        assert_eq!(eval("\"s\"/*c*/"), Ok(Val::from("s")));
+        assert_eq!(eval("[1/*one*/, 2][1]/*c*/"), Ok(Val::from(2)));
+        assert_eq!(eval("fn f x => x/* id */\nf 3"), Ok(Val::from(3)));
+    }
+
+    #[test]
+    fn eval_empty_program() {
+        assert_eq!(eval(""), Ok(Val::Unit));
+    }
+
+    #[test]
+    fn eval_stops_at_first_error() {
+        let mut env = Env::default();
+        let program = crate::parse("let a = 1\nlet b = c\nlet a = 2").unwrap();
+
+        assert!(program.eval(&mut env).is_err());
+        assert_eq!(env.get_binding("a"), Some(Val::from(1)));
+    }
+}
@@ -0,0 +1,36 @@
+use crate::error::{EvalError, Span};
+use crate::val::Val;
+
+/// Why evaluation stopped before producing a value. `break` and `continue`
+/// unwind to the nearest enclosing loop the same way errors unwind to the
+/// caller, but only errors make it out of a program.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Signal {
+    Error(EvalError),
+    Break { val: Val, span: Span },
+    Continue { span: Span },
+}
+
+impl Signal {
+    /// Called where a loop can no longer catch the signal, such as at a
+    /// function boundary or the top of the program.
+    pub(crate) fn into_error(self) -> EvalError {
+        match self {
+            Self::Error(error) => error,
+            Self::Break { span, .. } => EvalError::OutsideLoop {
+                keyword: "break",
+                span,
+            },
+            Self::Continue { span } => EvalError::OutsideLoop {
+                keyword: "continue",
+                span,
+            },
+        }
+    }
+}
+
+impl From<EvalError> for Signal {
+    fn from(error: EvalError) -> Self {
+        Self::Error(error)
+    }
+}
@@ -1,28 +1,89 @@
+use crate::assignment::Assignment;
 use crate::binding_def::BindingDef;
+use crate::closure::Tail;
 use crate::env::Env;
+use crate::error::{ParseError, Span};
 use crate::expr::Expr;
 use crate::func_def::FuncDef;
+use crate::signal::Signal;
+use crate::utils::{self, Input, OrParse};
 use crate::val::Val;
 
 #[derive(Debug, PartialEq, Clone)]
 pub(crate) enum Stmt {
     BindingDef(BindingDef),
     FuncDef(FuncDef),
+    Assignment(Assignment),
+    Break { val: Option<Expr>, span: Span },
+    Continue { span: Span },
     Expr(Expr),
 }
 
 impl Stmt {
-    pub(crate) fn new(s: &str) -> Result<(&str, Self), String> {
+    pub(crate) fn new(s: Input) -> Result<(Input, Self), ParseError> {
         BindingDef::new(s)
             .map(|(s, binding_def)| (s, Self::BindingDef(binding_def)))
-            .or_else(|_| FuncDef::new(s).map(|(s, func_def)| (s, Self::FuncDef(func_def))))
-            .or_else(|_| Expr::new(s).map(|(s, expr)| (s, Self::Expr(expr))))
+            .or_parse(|| FuncDef::new(s).map(|(s, func_def)| (s, Self::FuncDef(func_def))))
+            .or_parse(|| {
+                Assignment::new(s).map(|(s, assignment)| (s, Self::Assignment(assignment)))
+            })
+            .or_parse(|| Self::new_break(s))
+            .or_parse(|| Self::new_continue(s))
+            .or_parse(|| Expr::new(s).map(|(s, expr)| (s, Self::Expr(expr))))
+    }
+
+    /// The value of a `break` has to start on the same line.
+    fn new_break(s: Input) -> Result<(Input, Self), ParseError> {
+        let start = s;
+
+        let s = utils::keyword("break", s)?;
+        let span = start.span_to(s);
+
+        let (after_ws, _) = utils::extract_non_breaks(s);
+        match Expr::new(after_ws) {
+            Ok((s, val)) => Ok((
+                s,
+                Self::Break {
+                    val: Some(val),
+                    span,
+                },
+            )),
+            Err(error) if error.span.start > after_ws.offset() => Err(error),
+            Err(_) => Ok((s, Self::Break { val: None, span })),
+        }
+    }
+
+    fn new_continue(s: Input) -> Result<(Input, Self), ParseError> {
+        let rest = utils::keyword("continue", s)?;
+
+        Ok((
+            rest,
+            Self::Continue {
+                span: s.span_to(rest),
+            },
+        ))
     }
 
-    pub(crate) fn eval(&self, env: &mut Env) -> Result<Val, String> {
+    pub(crate) fn eval_tail(&self, env: &mut Env) -> Result<Tail, Signal> {
+        match self {
+            Self::Expr(ex) => ex.eval_tail(env),
+            stmt => stmt.eval(env).map(Tail::Val),
+        }
+    }
+
+    pub(crate) fn eval(&self, env: &mut Env) -> Result<Val, Signal> {
         match self {
             Self::BindingDef(bd) => bd.eval(env),
             Self::FuncDef(fd) => fd.eval(env),
+            Self::Assignment(assignment) => assignment.eval(env),
+            Self::Break { val, span } => {
+                let val = match val {
+                    Some(val) => val.eval(env)?,
+                    None => Val::Unit,
+                };
+                Err(Signal::Break { val, span: *span })
+            }
+            Self::Continue { span } => Err(Signal::Continue { span: *span }),
             Self::Expr(ex) => ex.eval(env),
         }
     }
@@ -30,6 +91,7 @@ impl Stmt {
 
 #[cfg(test)]
 mod tests {
+    use crate::error::EvalError;
     use crate::expr::{BindingUsage, Block, Number, Op};
 
     use super::*;
@@ -37,12 +99,12 @@ mod tests {
     #[test]
     fn parse_binding_def() {
         assert_eq!(
-            Stmt::new("let a = 10"),
+            Stmt::new("let a = 10".into()),
             Ok((
-                "",
+                "".into(),
                 Stmt::BindingDef(BindingDef {
                     name: "a".to_string(),
-                    val: Expr::Number(Number(10)),
+                    val: Expr::Number(Number::from(10)),
                 }),
             )),
         );
@@ -51,13 +113,14 @@ mod tests {
     #[test]
     fn parse_expr() {
         assert_eq!(
-            Stmt::new("1+1"),
+            Stmt::new("1+1".into()),
             Ok((
-                "",
+                "".into(),
                 Stmt::Expr(Expr::Operation {
-                    lhs: Box::new(Expr::Number(Number(1))),
-                    rhs: Box::new(Expr::Number(Number(1))),
+                    lhs: Box::new(Expr::Number(Number::from(1))),
+                    rhs: Box::new(Expr::Number(Number::from(1))),
                     op: Op::Add,
+                    span: Span::new(0, 3),
                 }),
             )),
         );
@@ -66,9 +129,9 @@ mod tests {
     #[test]
     fn parse_func_def_with_no_params_and_empty_body() {
         assert_eq!(
-            FuncDef::new("fn nothing => {}"),
+            FuncDef::new("fn nothing => {}".into()),
             Ok((
-                "",
+                "".into(),
                 FuncDef {
                     name: "nothing".to_string(),
                     params: Vec::new(),
@@ -81,9 +144,9 @@ mod tests {
     #[test]
     fn parse_func_def_with_one_param_and_empty_body() {
         assert_eq!(
-            FuncDef::new("fn greet name => {}"),
+            FuncDef::new("fn greet name => {}".into()),
             Ok((
-                "",
+                "".into(),
                 FuncDef {
                     name: "greet".to_string(),
                     params: vec!["name".to_string()],
@@ -96,37 +159,72 @@ mod tests {
     #[test]
     fn parse_func_def_with_multiple_params() {
         assert_eq!(
-            FuncDef::new("fn add x y => x + y"),
+            FuncDef::new("fn add x y => x + y".into()),
             Ok((
-                "",
+                "".into(),
                 FuncDef {
                     name: "add".to_string(),
                     params: vec!["x".to_string(), "y".to_string()],
                     body: Box::new(Stmt::Expr(Expr::Operation {
                         lhs: Box::new(Expr::BindingUsage(BindingUsage {
-                            name: "x".to_string()
+                            name: "x".to_string(),
+                            span: Span::new(14, 15),
                         })),
                         rhs: Box::new(Expr::BindingUsage(BindingUsage {
-                            name: "y".to_string()
+                            name: "y".to_string(),
+                            span: Span::new(18, 19),
                         })),
-                        op: Op::Add
+                        op: Op::Add,
+                        span: Span::new(14, 19),
                     }))
                 }
             ))
         );
     }
 
+    #[test]
+    fn parse_func_def_with_commented_params() {
+        assert_eq!(
+            FuncDef::new("fn first x # kept\n  /* ignored */ y => x".into()),
+            Ok((
+                "".into(),
+                FuncDef {
+                    name: "first".to_string(),
+                    params: vec!["x".to_string(), "y".to_string()],
+                    body: Box::new(Stmt::Expr(Expr::BindingUsage(BindingUsage {
+                        name: "x".to_string(),
+                        span: Span::new(39, 40),
+                    }))),
+                },
+            )),
+        );
+    }
+
+    #[test]
+    fn parse_func_def_with_invalid_param() {
+        assert_eq!(
+            FuncDef::new("fn add x 1 => x".into()),
+            Err(ParseError {
+                span: Span::new(9, 10),
+                line: 1,
+                column: 10,
+                expected: vec!["`=>`".to_string(), "identifier".to_string()],
+            }),
+        );
+    }
+
     #[test]
     fn parse_func_def() {
         assert_eq!(
-            Stmt::new("fn identity x => x"),
+            Stmt::new("fn identity x => x".into()),
             Ok((
-                "",
+                "".into(),
                 Stmt::FuncDef(FuncDef {
                     name: "identity".to_string(),
                     params: vec!["x".to_string()],
                     body: Box::new(Stmt::Expr(Expr::BindingUsage(BindingUsage {
                         name: "x".to_string(),
+                        span: Span::new(17, 18),
                     }))),
                 }),
             )),
@@ -138,18 +236,31 @@ mod tests {
         assert_eq!(
             Stmt::BindingDef(BindingDef {
                 name: "whatever".to_string(),
-                val: Expr::Number(Number(-10)),
+                val: Expr::Number(Number::from(-10)),
             })
             .eval(&mut Env::default()),
             Ok(Val::Unit),
         );
     }
 
+    #[test]
+    fn eval_names_starting_with_keywords() {
+        let mut env = Env::default();
+        let (_, def) = Stmt::new("let letter = 1".into()).unwrap();
+        let (_, assign) = Stmt::new("letter = iffy".into()).unwrap();
+        env.store_binding("iffy".to_string(), Val::from(2));
+
+        def.eval(&mut env).unwrap();
+        assign.eval(&mut env).unwrap();
+
+        assert_eq!(env.get_binding("letter"), Some(Val::from(2)));
+    }
+
     #[test]
     fn eval_expr() {
         assert_eq!(
-            Stmt::Expr(Expr::Number(Number(5))).eval(&mut Env::default()),
-            Ok(Val::Number(5)),
+            Stmt::Expr(Expr::Number(Number::from(5))).eval(&mut Env::default()),
+            Ok(Val::from(5)),
         );
     }
 
@@ -159,10 +270,123 @@ mod tests {
             Stmt::FuncDef(FuncDef {
                 name: "always_return_one".to_string(),
                 params: Vec::new(),
-                body: Box::new(Stmt::Expr(Expr::Number(Number(1)))),
+                body: Box::new(Stmt::Expr(Expr::Number(Number::from(1)))),
             })
             .eval(&mut Env::default()),
             Ok(Val::Unit),
         );
     }
+
+    #[test]
+    fn eval_recursive_func_with_if_else() {
+        let mut env = Env::default();
+        let (_, def) =
+            Stmt::new("fn fact n => if n < 2 { 1 } else { n * fact (n - 1) }".into()).unwrap();
+        def.eval(&mut env).unwrap();
+
+        let (_, call) = Stmt::new("fact 5".into()).unwrap();
+        assert_eq!(call.eval(&mut env), Ok(Val::from(120)));
+    }
+
+    #[test]
+    fn parse_break_with_value() {
+        assert_eq!(
+            Stmt::new("break 1".into()),
+            Ok((
+                "".into(),
+                Stmt::Break {
+                    val: Some(Expr::Number(Number::from(1))),
+                    span: Span::new(0, 5),
+                },
+            )),
+        );
+    }
+
+    #[test]
+    fn parse_break_without_value_before_new_line() {
+        assert_eq!(
+            Stmt::new("break\n1".into()),
+            Ok((
+                "\n1".into(),
+                Stmt::Break {
+                    val: None,
+                    span: Span::new(0, 5),
+                },
+            )),
+        );
+    }
+
+    #[test]
+    fn parse_break_with_invalid_value() {
+        assert_eq!(
+            Stmt::new("break 1 +".into()),
+            Err(ParseError {
+                span: Span::new(9, 9),
+                line: 1,
+                column: 10,
+                expected: vec![
+                    "`!`".to_string(),
+                    "`\"`".to_string(),
+                    "`(`".to_string(),
+                    "`-`".to_string(),
+                    "`[`".to_string(),
+                    "`false`".to_string(),
+                    "`for`".to_string(),
+                    "`if`".to_string(),
+                    "`loop`".to_string(),
+                    "`true`".to_string(),
+                    "`while`".to_string(),
+                    "`{`".to_string(),
+                    "`|`".to_string(),
+                    "identifier".to_string(),
+                    "number".to_string(),
+                ],
+            }),
+        );
+    }
+
+    #[test]
+    fn parse_continue() {
+        assert_eq!(
+            Stmt::new("continue".into()),
+            Ok((
+                "".into(),
+                Stmt::Continue {
+                    span: Span::new(0, 8),
+                },
+            )),
+        );
+    }
+
+    #[test]
+    fn eval_break_signals_enclosing_loop() {
+        assert_eq!(
+            Stmt::Break {
+                val: Some(Expr::Number(Number::from(3))),
+                span: Span::new(0, 5),
+            }
+            .eval(&mut Env::default()),
+            Err(Signal::Break {
+                val: Val::from(3),
+                span: Span::new(0, 5),
+            }),
+        );
+    }
+
+    #[test]
+    fn eval_break_does_not_escape_func() {
+        let mut env = Env::default();
+        let (_, def) = Stmt::new("fn stop => break".into()).unwrap();
+        def.eval(&mut env).unwrap();
+
+        let (_, stmt) = Stmt::new("loop { stop }".into()).unwrap();
+        assert_eq!(
+            stmt.eval(&mut env),
+            Err(EvalError::OutsideLoop {
+                keyword: "break",
+                span: Span::new(11, 16),
+            }
+            .into()),
+        );
+    }
 }
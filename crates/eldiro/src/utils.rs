@@ -1,61 +1,246 @@
-const WHITESPACE: &[char] = &[' ', '\n'];
+use std::sync::atomic::{AtomicUsize, Ordering};
 
-pub(crate) fn extract_digits(s: &str) -> Result<(&str, &str), String> {
-    take_while1(|c| c.is_ascii_digit(), s, "expected digits".to_string())
+use crate::error::{ParseError, Span};
+use crate::lexer::{self, Token, TokenKind};
+
+/// The part of the source that is still to be parsed, along with where it
+/// starts in the whole source.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Input<'a> {
+    source: &'a str,
+    /// Tells spans from different sources apart. Zero when the source was
+    /// not given one.
+    source_id: usize,
+    offset: usize,
+    line: usize,
+    column: usize,
+    /// How many expressions the parser is inside of.
+    depth: usize,
+}
+
+impl<'a> Input<'a> {
+    /// How deeply expressions can nest. The parser recurses for every level,
+    /// so without a limit deeply nested source would overflow the stack. This
+    /// many levels of the costliest kind, nested blocks, fit in the 2 MiB
+    /// stack of a spawned thread even in debug builds.
+    pub(crate) const MAX_DEPTH: usize = 64;
+
+    /// Input whose spans are told apart from those of any other source.
+    pub(crate) fn new_source(source: &'a str) -> Self {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(1);
+
+        Self {
+            source_id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            ..Self::from(source)
+        }
+    }
+
+    /// The whole source, including what has already been parsed.
+    pub(crate) fn source(&self) -> &'a str {
+        self.source
+    }
+
+    pub(crate) fn source_id(&self) -> usize {
+        self.source_id
+    }
+
+    pub(crate) fn as_str(&self) -> &'a str {
+        &self.source[self.offset..]
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.offset == self.source.len()
+    }
+
+    pub(crate) fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub(crate) fn line(&self) -> usize {
+        self.line
+    }
+
+    pub(crate) fn column(&self) -> usize {
+        self.column
+    }
+
+    /// The span between the start of `self` and the start of `rest`.
+    pub(crate) fn span_to(&self, rest: Self) -> Span {
+        Span {
+            source_id: self.source_id,
+            ..Span::new(self.offset, rest.offset)
+        }
+    }
+
+    /// The source text between the start of `self` and the start of `rest`.
+    pub(crate) fn text_to(&self, rest: Self) -> &'a str {
+        &self.source[self.offset..rest.offset]
+    }
+
+    /// Enters an expression nested in the current one, failing once that
+    /// would go past [`Self::MAX_DEPTH`].
+    pub(crate) fn nested(self) -> Result<Self, ParseError> {
+        if self.depth >= Self::MAX_DEPTH {
+            return Err(ParseError::new(
+                self,
+                format!("at most {} levels of nesting", Self::MAX_DEPTH),
+            ));
+        }
+
+        Ok(Self {
+            depth: self.depth + 1,
+            ..self
+        })
+    }
+
+    /// Leaves the expression entered with [`Self::nested`].
+    pub(crate) fn unnested(self) -> Self {
+        Self {
+            depth: self.depth - 1,
+            ..self
+        }
+    }
+
+    pub(crate) fn split_at(self, len: usize) -> (Self, &'a str) {
+        let (taken, _) = self.as_str().split_at(len);
+
+        let (mut line, mut column) = (self.line, self.column);
+        for c in taken.chars() {
+            if c == '\n' {
+                line += 1;
+                column = 1;
+            } else {
+                column += 1;
+            }
+        }
+
+        let rest = Self {
+            offset: self.offset + len,
+            line,
+            column,
+            ..self
+        };
+        (rest, taken)
+    }
+}
+
+impl<'a> From<&'a str> for Input<'a> {
+    fn from(source: &'a str) -> Self {
+        Self {
+            source,
+            source_id: 0,
+            offset: 0,
+            line: 1,
+            column: 1,
+            depth: 0,
+        }
+    }
+}
+
+/// Two inputs are equal when what is left to parse is the same.
+impl PartialEq for Input<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
 }
 
-pub(crate) fn extract_whitespace(s: &str) -> (&str, &str) {
-    take_while(|c| WHITESPACE.contains(&c), s)
+pub(crate) trait OrParse<'a, T> {
+    /// Tries `alternative` if `self` failed, keeping whichever failure got
+    /// further into the input.
+    fn or_parse(
+        self,
+        alternative: impl FnOnce() -> Result<(Input<'a>, T), ParseError>,
+    ) -> Result<(Input<'a>, T), ParseError>;
 }
 
-pub(crate) fn extract_non_breaks(s: &str) -> (&str, &str) {
-    take_while(|c| c == ' ', s)
+impl<'a, T> OrParse<'a, T> for Result<(Input<'a>, T), ParseError> {
+    fn or_parse(
+        self,
+        alternative: impl FnOnce() -> Result<(Input<'a>, T), ParseError>,
+    ) -> Result<(Input<'a>, T), ParseError> {
+        self.or_else(|error| alternative().map_err(|other| error.merge(other)))
+    }
 }
 
-pub(crate) fn extract_whitespace1(s: &str) -> Result<(&str, &str), String> {
-    take_while1(
-        |c| WHITESPACE.contains(&c),
+/// Lexes the token at the start of `s` and hands it to `accept`. A token that
+/// is rejected or does not lex is reported as `expected`, unless lexing failed
+/// past its start, as it does inside a malformed string.
+fn token<'a, T>(
+    s: Input<'a>,
+    expected: impl FnOnce() -> String,
+    accept: impl FnOnce(Token<'a>) -> Option<T>,
+) -> Result<(Input<'a>, T), ParseError> {
+    match lexer::next_token(s) {
+        Ok((rest, token)) => match accept(token) {
+            Some(accepted) => Ok((rest, accepted)),
+            None => Err(ParseError::new(s, expected())),
+        },
+        Err(error) if error.span.start > s.offset() => Err(error),
+        Err(_) => Err(ParseError::new(s, expected())),
+    }
+}
+
+/// Parses a number literal, returning it as written.
+pub(crate) fn extract_number(s: Input<'_>) -> Result<(Input<'_>, &str), ParseError> {
+    token(
         s,
-        "expected a space".to_string(),
+        || "number".to_string(),
+        |token| (token.kind == TokenKind::Number).then_some(token.text),
     )
 }
 
-pub(crate) fn extract_ident(s: &str) -> Result<(&str, &str), String> {
-    let starts_with_alphabetic = s
-        .chars()
-        .next()
-        .map(|c| c.is_ascii_alphabetic())
-        .unwrap_or(false);
-
-    if starts_with_alphabetic {
-        Ok(take_while(|c| c.is_ascii_alphanumeric(), s))
-    } else {
-        Err("expected identifier".to_string())
+pub(crate) fn extract_whitespace(s: Input<'_>) -> (Input<'_>, &str) {
+    let mut rest = s;
+    loop {
+        let (after_spaces, _) = lexer::skip_spaces(rest);
+        match extract_newline(after_spaces) {
+            Ok(after_newline) => rest = after_newline,
+            Err(_) => return (after_spaces, s.text_to(after_spaces)),
+        }
     }
 }
 
-fn take_while(accept: impl Fn(char) -> bool, s: &str) -> (&str, &str) {
-    let take_end = s
-        .char_indices()
-        .find_map(|(idx, c)| if accept(c) { None } else { Some(idx) })
-        .unwrap_or_else(|| s.len());
-    (&s[take_end..], &s[..take_end])
+fn extract_newline(s: Input<'_>) -> Result<Input<'_>, ParseError> {
+    token(
+        s,
+        || "a newline".to_string(),
+        |token| (token.kind == TokenKind::Newline).then_some(()),
+    )
+    .map(|(s, ())| s)
 }
 
-fn take_while1(
-    accept: impl Fn(char) -> bool,
-    s: &str,
-    error_msg: String,
-) -> Result<(&str, &str), String> {
-    let (remainder, extracted) = take_while(accept, s);
+pub(crate) fn extract_non_breaks(s: Input<'_>) -> (Input<'_>, &str) {
+    lexer::skip_spaces(s)
+}
 
-    if extracted.is_empty() {
-        Err(error_msg)
-    } else {
-        Ok((remainder, extracted))
+pub(crate) fn extract_whitespace1(s: Input<'_>) -> Result<(Input<'_>, &str), ParseError> {
+    match extract_whitespace(s) {
+        (_, "") => Err(ParseError::new(s, "a space")),
+        whitespace => Ok(whitespace),
     }
 }
 
+/// Parses a name. Keywords are reserved and never count as one.
+pub(crate) fn extract_ident(s: Input<'_>) -> Result<(Input<'_>, &str), ParseError> {
+    token(
+        s,
+        || "identifier".to_string(),
+        |token| (token.kind == TokenKind::Ident).then_some(token.text),
+    )
+}
+
+/// Parses a double-quoted string literal, with its escape sequences resolved.
+pub(crate) fn extract_string(s: Input<'_>) -> Result<(Input<'_>, String), ParseError> {
+    token(
+        s,
+        || "`\"`".to_string(),
+        |token| match token.kind {
+            TokenKind::Str(string) => Some(string),
+            _ => None,
+        },
+    )
+}
+
 /*
 Those lifetimes on tag might look scary,
 but all they’re doing is telling Rust that
@@ -66,93 +251,365 @@ If we didn’t have the lifetimes,
 Rust wouldn’t know if the returned value has to live
 as long as prefix, or s, or both.
 */
-pub(crate) fn tag<'a, 'b>(prefix: &'a str, s: &'b str) -> Result<&'b str, String> {
-    if s.starts_with(prefix) {
-        Ok(&s[prefix.len()..])
-    } else {
-        Err(format!("expected {}", prefix))
-    }
+#[allow(clippy::needless_lifetimes)]
+pub(crate) fn tag<'a, 'b>(prefix: &'a str, s: Input<'b>) -> Result<Input<'b>, ParseError> {
+    token(
+        s,
+        || format!("`{}`", prefix),
+        |token| (token.kind == TokenKind::Punct && token.text == prefix).then_some(()),
+    )
+    .map(|(s, ())| s)
 }
 
-pub(crate) fn sequence<T>(
-    parser: impl Fn(&str) -> Result<(&str, T), String>,
-    mut s: &str,
-    whitespace_parser: Option<Box<dyn Fn(&str) -> (&str, &str)>>,
-) -> Result<(&str, Vec<T>), String> {
+/// Like `tag`, but for a reserved word such as `true` or `let`.
+pub(crate) fn keyword<'a>(keyword: &str, s: Input<'a>) -> Result<Input<'a>, ParseError> {
+    token(
+        s,
+        || format!("`{}`", keyword),
+        |token| (token.kind == TokenKind::Keyword && token.text == keyword).then_some(()),
+    )
+    .map(|(s, ())| s)
+}
+
+pub(crate) type WhitespaceParser = Box<dyn Fn(Input<'_>) -> (Input<'_>, &str)>;
+
+/// Parses any number of items, each preceded by whitespace. Whitespace after
+/// the last item is left unparsed.
+pub(crate) fn sequence<'a, T>(
+    parser: impl Fn(Input<'a>) -> Result<(Input<'a>, T), ParseError>,
+    mut s: Input<'a>,
+    whitespace_parser: Option<WhitespaceParser>,
+) -> Result<(Input<'a>, Vec<T>), ParseError> {
     let mut items = Vec::new();
     let whitespace_parser = whitespace_parser.unwrap_or(Box::new(extract_whitespace));
 
-    while let Ok((new_s, item)) = parser(s) {
+    while let Ok((new_s, item)) = parser(whitespace_parser(s).0) {
         items.push(item);
-        let (new_s, _) = whitespace_parser(new_s);
         s = new_s;
     }
 
     Ok((s, items))
 }
 
+/// Parses whitespace-separated items up to and including `close`. If `close`
+/// is missing, the error also says why the next item could not be parsed.
+pub(crate) fn sequence_until<'a, T>(
+    parser: impl Fn(Input<'a>) -> Result<(Input<'a>, T), ParseError>,
+    close: &str,
+    mut s: Input<'a>,
+) -> Result<(Input<'a>, Vec<T>), ParseError> {
+    let mut items = Vec::new();
+
+    loop {
+        match parser(s) {
+            Ok((new_s, item)) => {
+                items.push(item);
+                let (new_s, _) = extract_whitespace(new_s);
+                s = new_s;
+            }
+            Err(error) => {
+                return tag(close, s)
+                    .map(|s| (s, items))
+                    .map_err(|close_error| error.merge(close_error))
+            }
+        }
+    }
+}
+
+/// Parses items that are each followed by a newline or `;`, up to and
+/// including `close`, or up to the end of input when there is no `close`.
+/// The last item needs no separator.
+pub(crate) fn separated_until<'a, T>(
+    parser: impl Fn(Input<'a>) -> Result<(Input<'a>, T), ParseError>,
+    close: Option<&str>,
+    s: Input<'a>,
+) -> Result<(Input<'a>, Vec<T>), ParseError> {
+    let finish = |s: Input<'a>| match close {
+        Some(close) => tag(close, s),
+        None if s.is_empty() => Ok(s),
+        None => Err(ParseError::new(s, "the end of input")),
+    };
+
+    let mut items = Vec::new();
+    let (mut s, _) = extract_whitespace(s);
+
+    loop {
+        if let Ok(rest) = finish(s) {
+            return Ok((rest, items));
+        }
+
+        let (rest, item) = parser(s).map_err(|error| match close {
+            Some(close) => error.merge(tag(close, s).unwrap_err()),
+            None => error,
+        })?;
+        items.push(item);
+
+        s = extract_separators(rest).or_else(|error| {
+            let (rest, _) = extract_non_breaks(rest);
+            match finish(rest) {
+                Ok(_) => Ok(rest),
+                Err(close_error) if close.is_some() => Err(error.merge(close_error)),
+                Err(_) => Err(error),
+            }
+        })?;
+    }
+}
+
+/// One or more newlines or `;`, along with the whitespace around them.
+fn extract_separators(s: Input) -> Result<Input, ParseError> {
+    let (s, _) = extract_non_breaks(s);
+    let mut s = extract_separator(s)?;
+
+    loop {
+        let (after_ws, _) = extract_non_breaks(s);
+        match extract_separator(after_ws) {
+            Ok(rest) => s = rest,
+            Err(_) => return Ok(after_ws),
+        }
+    }
+}
+
+fn extract_separator(s: Input) -> Result<Input, ParseError> {
+    tag(";", s).or_else(|error| extract_newline(s).map_err(|other| error.merge(other)))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error::Span;
 
     #[test]
-    fn extract_one_digit() {
-        assert_eq!(extract_digits("1+2"), Ok(("+2", "1")));
+    fn extract_one_digit_number() {
+        assert_eq!(extract_number("1+2".into()), Ok(("+2".into(), "1")));
     }
 
     #[test]
-    fn extract_multiple_digits() {
-        assert_eq!(extract_digits("10-20"), Ok(("-20", "10")));
+    fn extract_multiple_digit_number() {
+        assert_eq!(extract_number("10-20".into()), Ok(("-20".into(), "10")));
     }
 
     #[test]
-    fn do_not_extract_digits_from_invalid_input() {
-        assert_eq!(extract_digits("abcd"), Err("expected digits".to_string()));
+    fn do_not_extract_number_from_invalid_input() {
+        assert_eq!(
+            extract_number("abcd".into()),
+            Err(ParseError::new("abcd".into(), "number")),
+        );
     }
 
     #[test]
-    fn extract_digits_with_no_remainder() {
-        assert_eq!(extract_digits("100"), Ok(("", "100")));
+    fn extract_number_with_no_remainder() {
+        assert_eq!(extract_number("100".into()), Ok(("".into(), "100")));
     }
 
     #[test]
     fn extract_spaces() {
-        assert_eq!(extract_whitespace("    1"), ("1", "    "));
+        assert_eq!(extract_whitespace("    1".into()), ("1".into(), "    "));
     }
 
     #[test]
     fn extract_newlines_or_spaces() {
-        assert_eq!(extract_whitespace(" \n   \n\nabc"), ("abc", " \n   \n\n"));
+        assert_eq!(
+            extract_whitespace(" \n   \n\nabc".into()),
+            ("abc".into(), " \n   \n\n"),
+        );
     }
 
     #[test]
     fn do_not_extract_spaces1_when_input_does_not_start_with_them() {
         assert_eq!(
-            extract_whitespace1("blah"),
-            Err("expected a space".to_string()),
+            extract_whitespace1("blah".into()),
+            Err(ParseError::new("blah".into(), "a space")),
         );
     }
 
     #[test]
     fn extract_alphabetic_ident() {
-        assert_eq!(extract_ident("abcdEFG stop"), Ok((" stop", "abcdEFG")));
+        assert_eq!(
+            extract_ident("abcdEFG stop".into()),
+            Ok((" stop".into(), "abcdEFG")),
+        );
     }
 
     #[test]
     fn extract_alphanumeric_ident() {
-        assert_eq!(extract_ident("bazbleh13()"), Ok(("()", "bazbleh13")));
+        assert_eq!(
+            extract_ident("bazbleh13()".into()),
+            Ok(("()".into(), "bazbleh13")),
+        );
     }
 
     #[test]
     fn will_not_extract_ident_beginning_with_number() {
         assert_eq!(
-            extract_ident("123abc"),
-            Err("expected identifier".to_string()),
+            extract_ident("123abc".into()),
+            Err(ParseError::new("123abc".into(), "identifier")),
+        );
+    }
+
+    #[test]
+    fn extract_number_with_fraction_and_exponent() {
+        assert_eq!(extract_number("1.5e3.x".into()), Ok((".x".into(), "1.5e3")));
+    }
+
+    #[test]
+    fn will_not_extract_keyword_as_ident() {
+        assert_eq!(
+            extract_ident("let".into()),
+            Err(ParseError::new("let".into(), "identifier")),
+        );
+    }
+
+    #[test]
+    fn extract_ident_starting_with_keyword() {
+        assert_eq!(
+            extract_ident("letter = 1".into()),
+            Ok((" = 1".into(), "letter"))
+        );
+    }
+
+    #[test]
+    fn tag_punctuation() {
+        assert_eq!(tag("==", "== a".into()), Ok(" a".into()))
+    }
+
+    #[test]
+    fn tag_does_not_match_start_of_longer_punctuation() {
+        assert_eq!(
+            tag("=", "==".into()),
+            Err(ParseError::new("==".into(), "`=`")),
+        );
+    }
+
+    #[test]
+    fn keyword_followed_by_space() {
+        assert_eq!(keyword("true", "true)".into()), Ok(")".into()));
+    }
+
+    #[test]
+    fn keyword_does_not_match_identifier_prefix() {
+        assert_eq!(
+            keyword("true", "trueish".into()),
+            Err(ParseError::new("trueish".into(), "`true`")),
+        );
+    }
+
+    #[test]
+    fn track_position_across_lines() {
+        let (s, _) = extract_whitespace(" \n  \n   abc".into());
+
+        assert_eq!((s.offset(), s.line(), s.column()), (8, 3, 4));
+    }
+
+    #[test]
+    fn error_points_at_failure_position() {
+        let (s, _) = extract_whitespace("\n  123".into());
+
+        assert_eq!(
+            extract_ident(s),
+            Err(ParseError {
+                span: Span::new(3, 4),
+                line: 2,
+                column: 3,
+                expected: vec!["identifier".to_string()],
+            }),
         );
     }
 
     #[test]
-    fn tag_word() {
-        assert_eq!(tag("let", "let a"), Ok(" a"))
+    fn or_parse_keeps_furthest_error() {
+        let s = Input::from("a1 b");
+        let ident_then_space = |s| {
+            let (s, ident) = extract_ident(s)?;
+            let (s, _) = extract_whitespace1(s)?;
+            Ok((s, ident))
+        };
+
+        assert_eq!(
+            extract_number(s).or_parse(|| ident_then_space(s)),
+            Ok(("b".into(), "a1")),
+        );
+        assert_eq!(
+            extract_number("ab".into()).or_parse(|| ident_then_space("ab".into())),
+            Err(ParseError {
+                span: Span::new(2, 2),
+                line: 1,
+                column: 3,
+                expected: vec!["a space".to_string()],
+            }),
+        );
+    }
+
+    #[test]
+    fn sequence_until_reports_failed_item() {
+        assert_eq!(
+            sequence_until(extract_number, ")", "1 2 x)".into()),
+            Err(ParseError {
+                span: Span::new(4, 5),
+                line: 1,
+                column: 5,
+                expected: vec!["`)`".to_string(), "number".to_string()],
+            }),
+        );
+    }
+
+    #[test]
+    fn extract_plain_string() {
+        assert_eq!(
+            extract_string(r#""hello" rest"#.into()),
+            Ok((" rest".into(), "hello".to_string())),
+        );
+    }
+
+    #[test]
+    fn extract_string_with_escapes() {
+        assert_eq!(
+            extract_string(r#""a\nb\t\"c\" \\ \u{e9}\u{1F600}""#.into()),
+            Ok(("".into(), "a\nb\t\"c\" \\ \u{e9}\u{1F600}".to_string())),
+        );
+    }
+
+    #[test]
+    fn extract_unterminated_string() {
+        assert_eq!(
+            extract_string(r#""abc"#.into()),
+            Err(ParseError {
+                span: Span::new(4, 4),
+                line: 1,
+                column: 5,
+                expected: vec!["`\"`".to_string()],
+            }),
+        );
+    }
+
+    #[test]
+    fn extract_string_with_unknown_escape() {
+        assert_eq!(
+            extract_string(r#""a\qb""#.into()),
+            Err(ParseError {
+                span: Span::new(3, 4),
+                line: 1,
+                column: 4,
+                expected: vec![
+                    "`\"`".to_string(),
+                    "`\\`".to_string(),
+                    "`n`".to_string(),
+                    "`t`".to_string(),
+                    "`u{`".to_string(),
+                ],
+            }),
+        );
+    }
+
+    #[test]
+    fn extract_string_with_invalid_code_point() {
+        assert_eq!(
+            extract_string(r#""\u{d800}""#.into()),
+            Err(ParseError {
+                span: Span::new(4, 8),
+                line: 1,
+                column: 5,
+                expected: vec!["a valid unicode code point".to_string()],
+            }),
+        );
     }
 }
@@ -1,16 +1,421 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
 use std::fmt;
+use std::rc::Rc;
+
+use crate::closure::Closure;
+use crate::error::{ConversionError, EvalError, Span};
+use crate::int::{self, Int};
+use crate::native::NativeFunc;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Val {
-    Number(i32),
+    Number(Int),
+    Float(f64),
+    Bool(bool),
+    Str(String),
+    List(Vec<Val>),
+    /// Kept sorted by key, so that iterating and printing a map is
+    /// deterministic.
+    Map(BTreeMap<String, Val>),
+    Func(Rc<Closure>),
+    Native(Rc<NativeFunc>),
     Unit,
 }
 
+impl Val {
+    /// The name scripts see in type errors, such as `"number"` or `"list"`.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Self::Number(_) => "number",
+            Self::Float(_) => "float",
+            Self::Bool(_) => "bool",
+            Self::Str(_) => "string",
+            Self::List(_) => "list",
+            Self::Map(_) => "map",
+            Self::Func(_) | Self::Native(_) => "function",
+            Self::Unit => "unit",
+        }
+    }
+
+    pub(crate) fn as_num(&self) -> Option<Num> {
+        match self {
+            Self::Number(n) => Some(Num::Int(int::duplicate(n))),
+            Self::Float(f) => Some(Num::Float(*f)),
+            _ => None,
+        }
+    }
+
+    /// Accepts integers as well as floats.
+    pub(crate) fn expect_num(self, span: Span) -> Result<Num, EvalError> {
+        self.as_num().ok_or_else(|| EvalError::TypeMismatch {
+            expected: "number",
+            found: self.type_name(),
+            span,
+        })
+    }
+
+    /// Accepts integers only, unlike [`Self::expect_num`].
+    pub(crate) fn expect_int(self, span: Span) -> Result<Int, EvalError> {
+        match self {
+            Self::Number(n) => Ok(n),
+            other => Err(EvalError::TypeMismatch {
+                expected: "number",
+                found: other.type_name(),
+                span,
+            }),
+        }
+    }
+
+    pub(crate) fn expect_str(self, span: Span) -> Result<String, EvalError> {
+        match self {
+            Self::Str(s) => Ok(s),
+            other => Err(EvalError::TypeMismatch {
+                expected: "string",
+                found: other.type_name(),
+                span,
+            }),
+        }
+    }
+
+    pub(crate) fn expect_list(self, span: Span) -> Result<Vec<Val>, EvalError> {
+        match self {
+            Self::List(items) => Ok(items),
+            other => Err(EvalError::TypeMismatch {
+                expected: "list",
+                found: other.type_name(),
+                span,
+            }),
+        }
+    }
+
+    pub(crate) fn expect_map(self, span: Span) -> Result<BTreeMap<String, Val>, EvalError> {
+        match self {
+            Self::Map(entries) => Ok(entries),
+            other => Err(EvalError::TypeMismatch {
+                expected: "map",
+                found: other.type_name(),
+                span,
+            }),
+        }
+    }
+
+    pub(crate) fn expect_bool(self, span: Span) -> Result<bool, EvalError> {
+        match self {
+            Self::Bool(b) => Ok(b),
+            other => Err(EvalError::TypeMismatch {
+                expected: "bool",
+                found: other.type_name(),
+                span,
+            }),
+        }
+    }
+}
+
 impl fmt::Display for Val {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::Number(n) => write!(f, "{}", n),
+            // Debug formatting keeps the `.0` of whole floats.
+            Self::Float(x) => write!(f, "{:?}", x),
+            Self::Bool(b) => write!(f, "{}", b),
+            Self::Str(s) => write!(f, "{:?}", s),
+            Self::List(items) => {
+                write!(f, "[")?;
+                for (idx, item) in items.iter().enumerate() {
+                    if idx > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            }
+            Self::Map(entries) if entries.is_empty() => write!(f, "{{:}}"),
+            Self::Map(entries) => {
+                write!(f, "{{")?;
+                for (idx, (key, val)) in entries.iter().enumerate() {
+                    if idx > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{:?}: {}", key, val)?;
+                }
+                write!(f, "}}")
+            }
+            Self::Func(closure) => write!(f, "<function |{}|>", closure.params.join(" ")),
+            Self::Native(native) => write!(f, "<function {}>", native.name),
             Self::Unit => write!(f, "Unit"),
         }
     }
 }
+
+impl From<i32> for Val {
+    fn from(n: i32) -> Self {
+        Self::Number(Int::from(n))
+    }
+}
+
+impl From<Int> for Val {
+    fn from(n: Int) -> Self {
+        Self::Number(n)
+    }
+}
+
+#[cfg(feature = "bigint")]
+impl From<i64> for Val {
+    fn from(n: i64) -> Self {
+        Self::Number(Int::from(n))
+    }
+}
+
+impl From<f64> for Val {
+    fn from(f: f64) -> Self {
+        Self::Float(f)
+    }
+}
+
+impl From<bool> for Val {
+    fn from(b: bool) -> Self {
+        Self::Bool(b)
+    }
+}
+
+impl From<String> for Val {
+    fn from(s: String) -> Self {
+        Self::Str(s)
+    }
+}
+
+impl From<&str> for Val {
+    fn from(s: &str) -> Self {
+        Self::Str(s.to_string())
+    }
+}
+
+impl From<()> for Val {
+    fn from(_: ()) -> Self {
+        Self::Unit
+    }
+}
+
+impl<T: Into<Val>> From<Vec<T>> for Val {
+    fn from(items: Vec<T>) -> Self {
+        Self::List(items.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<T: Into<Val>> From<BTreeMap<String, T>> for Val {
+    fn from(entries: BTreeMap<String, T>) -> Self {
+        Self::Map(
+            entries
+                .into_iter()
+                .map(|(key, val)| (key, val.into()))
+                .collect(),
+        )
+    }
+}
+
+/// Implements `TryFrom<Val>` for a Rust type by matching one variant.
+macro_rules! try_from_val {
+    ($ty:ty, $expected:literal, $pattern:pat => $out:expr) => {
+        impl TryFrom<Val> for $ty {
+            type Error = ConversionError;
+
+            fn try_from(val: Val) -> Result<Self, Self::Error> {
+                match val {
+                    $pattern => Ok($out),
+                    other => Err(ConversionError::TypeMismatch {
+                        expected: $expected,
+                        found: other.type_name(),
+                    }),
+                }
+            }
+        }
+    };
+}
+
+try_from_val!(Int, "number", Val::Number(n) => n);
+try_from_val!(bool, "bool", Val::Bool(b) => b);
+try_from_val!(String, "string", Val::Str(s) => s);
+try_from_val!(Vec<Val>, "list", Val::List(items) => items);
+try_from_val!(BTreeMap<String, Val>, "map", Val::Map(entries) => entries);
+try_from_val!((), "unit", Val::Unit => ());
+
+macro_rules! try_from_val_int {
+    ($($ty:ty),*) => {
+        $(
+            impl TryFrom<Val> for $ty {
+                type Error = ConversionError;
+
+                fn try_from(val: Val) -> Result<Self, Self::Error> {
+                    <$ty>::try_from(Int::try_from(val)?).map_err(|_| {
+                        ConversionError::OutOfRange {
+                            target: stringify!($ty),
+                        }
+                    })
+                }
+            }
+        )*
+    };
+}
+
+// Without `bigint`, `Int` is `i64` and already converted above.
+#[cfg(not(feature = "bigint"))]
+try_from_val_int!(i32, usize);
+#[cfg(feature = "bigint")]
+try_from_val_int!(i64, i32, usize);
+
+/// Integers are accepted as well, as in arithmetic.
+impl TryFrom<Val> for f64 {
+    type Error = ConversionError;
+
+    fn try_from(val: Val) -> Result<Self, Self::Error> {
+        val.as_num()
+            .map(|num| num.as_f64())
+            .ok_or(ConversionError::TypeMismatch {
+                expected: "number",
+                found: val.type_name(),
+            })
+    }
+}
+
+/// A value that arithmetic and comparisons accept. An integer is promoted to
+/// a float when it meets one, so `1 == 1.0`.
+#[derive(Debug, Clone)]
+pub(crate) enum Num {
+    Int(Int),
+    Float(f64),
+}
+
+impl Num {
+    pub(crate) fn as_f64(&self) -> f64 {
+        match self {
+            Self::Int(n) => int::to_f64(n),
+            Self::Float(f) => *f,
+        }
+    }
+}
+
+impl PartialEq for Num {
+    fn eq(&self, other: &Self) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for Num {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (Self::Int(lhs), Self::Int(rhs)) => lhs.partial_cmp(rhs),
+            (lhs, rhs) => lhs.as_f64().partial_cmp(&rhs.as_f64()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_str_quoted_with_escapes() {
+        assert_eq!(
+            Val::Str("say \"hi\"\n\tbye".to_string()).to_string(),
+            r#""say \"hi\"\n\tbye""#,
+        );
+    }
+
+    #[test]
+    fn display_whole_float_with_fraction() {
+        assert_eq!(Val::Float(3.0).to_string(), "3.0");
+        assert_eq!(Val::Float(1e-9).to_string(), "1e-9");
+    }
+
+    #[test]
+    fn display_nested_list() {
+        let list = Val::List(vec![
+            Val::Bool(true),
+            Val::List(vec![Val::Str("a".to_string()), Val::Unit]),
+            Val::List(Vec::new()),
+        ]);
+
+        assert_eq!(list.to_string(), r#"[true, ["a", Unit], []]"#);
+    }
+
+    #[test]
+    fn display_empty_map() {
+        assert_eq!(Val::Map(BTreeMap::new()).to_string(), "{:}");
+    }
+
+    #[test]
+    fn convert_rust_values_into_vals() {
+        assert_eq!(Val::from(3), Val::Number(Int::from(3)));
+        assert_eq!(Val::from(0.5), Val::Float(0.5));
+        assert_eq!(Val::from("hi"), Val::Str("hi".to_string()));
+        assert_eq!(Val::from(()), Val::Unit);
+        assert_eq!(
+            Val::from(vec![true, false]),
+            Val::List(vec![Val::Bool(true), Val::Bool(false)]),
+        );
+
+        let entries = BTreeMap::from([("a".to_string(), 1)]);
+        assert_eq!(
+            Val::from(entries),
+            Val::Map(BTreeMap::from([("a".to_string(), Val::from(1))])),
+        );
+    }
+
+    #[test]
+    fn convert_vals_into_rust_values() {
+        assert_eq!(Int::try_from(Val::from(7)), Ok(Int::from(7)));
+        assert_eq!(f64::try_from(Val::from(7)), Ok(7.0));
+        assert_eq!(String::try_from(Val::from("s")), Ok("s".to_string()));
+        assert_eq!(
+            Vec::<Val>::try_from(Val::from(vec![1])),
+            Ok(vec![Val::from(1)]),
+        );
+    }
+
+    #[test]
+    fn convert_val_of_wrong_type() {
+        assert_eq!(
+            bool::try_from(Val::from(1.5)),
+            Err(ConversionError::TypeMismatch {
+                expected: "bool",
+                found: "float",
+            }),
+        );
+    }
+
+    #[test]
+    fn convert_vals_into_fixed_size_integers() {
+        assert_eq!(i64::try_from(Val::from(-7)), Ok(-7));
+        assert_eq!(i32::try_from(Val::from(7)), Ok(7));
+        assert_eq!(usize::try_from(Val::from(7)), Ok(7));
+        assert_eq!(
+            usize::try_from(Val::from("7")),
+            Err(ConversionError::TypeMismatch {
+                expected: "number",
+                found: "string",
+            }),
+        );
+    }
+
+    #[test]
+    fn convert_val_out_of_range() {
+        assert_eq!(
+            usize::try_from(Val::from(-1)),
+            Err(ConversionError::OutOfRange { target: "usize" }),
+        );
+        assert_eq!(
+            i32::try_from(Val::from(i64::MAX)),
+            Err(ConversionError::OutOfRange { target: "i32" }),
+        );
+    }
+
+    #[cfg(feature = "bigint")]
+    #[test]
+    fn convert_big_val_out_of_range() {
+        assert_eq!(
+            i64::try_from(Val::Number(Int::from(i64::MAX) + 1)),
+            Err(ConversionError::OutOfRange { target: "i64" }),
+        );
+    }
+}